@@ -0,0 +1,338 @@
+//! Music and sound effects.
+//!
+//! Every sound is synthesized once at startup. Music follows [GameState] and crossfades
+//! between the tracks, while effects are requested with [PlaySound] or played in response to
+//! the game events. Loudness of both is controlled by [Volume], which is set in the settings
+//! and kept in the save file.
+
+mod synth;
+
+use bevy::{audio::AddAudioSource, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        cargo::{Cargo, CargoBroken, Shock},
+        map::world_to_tile,
+        order::OrderEvent,
+        player::{Footstep, Player},
+        simulation::SimulationSystems,
+    },
+    map::{
+        tile::{DecorationTile, TileClass},
+        MapData,
+    },
+    GameState,
+};
+
+use self::synth::Synth;
+
+/// Time it takes for the music to fade in or out.
+const CROSSFADE_TIME: f32 = 1.5;
+/// Smallest shock that makes the bottles clink.
+const CLINK_SHOCK: f32 = 0.05;
+/// Largest relative change of the speed of sounds that are repeated often.
+const PITCH_VARIATION: f32 = 0.1;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<Synth>()
+            .init_resource::<Volume>()
+            .init_resource::<Music>()
+            .add_event::<PlaySound>()
+            .add_startup_system(setup)
+            .add_systems((switch_music, fade_music).chain())
+            // Cargo events only last for a couple of steps, which may all run in a single frame.
+            .add_step_system(cargo_sounds)
+            .add_systems((footsteps, play_sounds).chain());
+    }
+}
+
+/// Sound effect of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    Footstep(Surface),
+    Hover,
+    Click,
+    Clink,
+    Shatter,
+}
+
+impl Sound {
+    /// Loudness of the sound relative to the others.
+    fn gain(&self) -> f32 {
+        match self {
+            Sound::Footstep(_) => 0.4,
+            Sound::Hover => 0.6,
+            Sound::Click | Sound::Clink | Sound::Shatter => 1.,
+        }
+    }
+
+    /// Whether the sound is played with slightly different pitch every time.
+    fn varies(&self) -> bool {
+        matches!(self, Sound::Footstep(_) | Sound::Clink)
+    }
+}
+
+/// Ground that the courier walks on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    Pavement,
+    Asphalt,
+    Grass,
+}
+
+impl Surface {
+    /// Returns surface at provided world position.
+    fn at(map: &MapData<DecorationTile>, position: Vec2) -> Self {
+        let tile = world_to_tile(map.size(), position).and_then(|(x, y)| map.get(x, y));
+        match tile {
+            Some(tile) if tile.road() => Surface::Asphalt,
+            Some(tile) if tile.grass() => Surface::Grass,
+            _ => Surface::Pavement,
+        }
+    }
+
+    /// How much the surface muffles the steps, from 0 to 1.
+    fn softness(&self) -> f32 {
+        match self {
+            Surface::Pavement => 0.,
+            Surface::Asphalt => 0.4,
+            Surface::Grass => 1.,
+        }
+    }
+}
+
+/// Requests sound effect to be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaySound(pub Sound);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Master,
+    Music,
+    Effects,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 3] = [Channel::Master, Channel::Music, Channel::Effects];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Master => "Master",
+            Channel::Music => "Music",
+            Channel::Effects => "Effects",
+        }
+    }
+}
+
+/// Volume of every channel, from 0 to 1.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Volume {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.6,
+            effects: 0.8,
+        }
+    }
+}
+
+impl Volume {
+    pub fn get(&self, channel: Channel) -> f32 {
+        match channel {
+            Channel::Master => self.master,
+            Channel::Music => self.music,
+            Channel::Effects => self.effects,
+        }
+    }
+
+    /// Changes volume of the channel by provided amount, keeping it between 0 and 1.
+    pub fn change(&mut self, channel: Channel, amount: f32) {
+        let volume = match channel {
+            Channel::Master => &mut self.master,
+            Channel::Music => &mut self.music,
+            Channel::Effects => &mut self.effects,
+        };
+        // Rounding keeps repeated steps from drifting away from the round values.
+        *volume = ((*volume + amount) * 100.).round().clamp(0., 100.) / 100.;
+    }
+
+    /// Volume that the channel is played with, including the master volume.
+    pub fn level(&self, channel: Channel) -> f32 {
+        match channel {
+            Channel::Master => self.master,
+            channel => self.master * self.get(channel),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Sounds {
+    pavement: Handle<Synth>,
+    asphalt: Handle<Synth>,
+    grass: Handle<Synth>,
+    hover: Handle<Synth>,
+    click: Handle<Synth>,
+    clink: Handle<Synth>,
+    shatter: Handle<Synth>,
+    menu_music: Handle<Synth>,
+    game_music: Handle<Synth>,
+}
+
+impl Sounds {
+    fn effect(&self, sound: Sound) -> Handle<Synth> {
+        match sound {
+            Sound::Footstep(Surface::Pavement) => self.pavement.clone(),
+            Sound::Footstep(Surface::Asphalt) => self.asphalt.clone(),
+            Sound::Footstep(Surface::Grass) => self.grass.clone(),
+            Sound::Hover => self.hover.clone(),
+            Sound::Click => self.click.clone(),
+            Sound::Clink => self.clink.clone(),
+            Sound::Shatter => self.shatter.clone(),
+        }
+    }
+
+    fn music(&self, state: &GameState) -> Handle<Synth> {
+        match state {
+            GameState::MainMenu => self.menu_music.clone(),
+            GameState::Game => self.game_music.clone(),
+        }
+    }
+}
+
+/// Music track that is playing right now.
+struct Track {
+    sink: Handle<AudioSink>,
+    /// Volume of the track relative to the music channel.
+    level: f32,
+    /// Whether the track fades in, otherwise it fades out and stops.
+    current: bool,
+}
+
+#[derive(Resource, Default)]
+struct Music(Vec<Track>);
+
+fn setup(mut commands: Commands, mut synths: ResMut<Assets<Synth>>) {
+    let mut add = |synth| synths.add(synth);
+    commands.insert_resource(Sounds {
+        pavement: add(synth::footstep(Surface::Pavement.softness())),
+        asphalt: add(synth::footstep(Surface::Asphalt.softness())),
+        grass: add(synth::footstep(Surface::Grass.softness())),
+        hover: add(synth::hover()),
+        click: add(synth::click()),
+        clink: add(synth::clink()),
+        shatter: add(synth::shatter()),
+        menu_music: add(synth::menu_music()),
+        game_music: add(synth::game_music()),
+    });
+}
+
+/// Starts music of the new state, which fades in while the previous one fades out.
+fn switch_music(
+    state: Res<State<GameState>>,
+    sounds: Res<Sounds>,
+    audio: Res<Audio<Synth>>,
+    sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<Music>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for track in &mut music.0 {
+        track.current = false;
+    }
+    let sink = audio.play_with_settings(
+        sounds.music(&state.0),
+        PlaybackSettings::LOOP.with_volume(0.),
+    );
+    // Sink only lives as long as there is a strong handle to it.
+    music.0.push(Track {
+        sink: sinks.get_handle(sink),
+        level: 0.,
+        current: true,
+    });
+}
+
+fn fade_music(
+    time: Res<Time>,
+    volume: Res<Volume>,
+    sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<Music>,
+) {
+    let step = time.delta_seconds() / CROSSFADE_TIME;
+    for track in &mut music.0 {
+        let target = if track.current { 1. } else { 0. };
+        track.level += (target - track.level).clamp(-step, step);
+        let Some(sink) = sinks.get(&track.sink) else {
+            continue;
+        };
+        sink.set_volume(track.level * volume.level(Channel::Music));
+        if !track.current && track.level <= 0. {
+            sink.stop();
+        }
+    }
+    music.0.retain(|track| track.current || track.level > 0.);
+}
+
+fn footsteps(
+    mut steps: EventReader<Footstep>,
+    maps: Query<&MapData<DecorationTile>>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    let Ok(map) = maps.get_single() else {
+        steps.clear();
+        return;
+    };
+    for step in steps.iter() {
+        sounds.send(PlaySound(Sound::Footstep(Surface::at(map, step.0))));
+    }
+}
+
+/// Fragile cargo clinks when it's shaken or handed over and shatters when it breaks.
+fn cargo_sounds(
+    mut shocks: EventReader<Shock>,
+    mut broken: EventReader<CargoBroken>,
+    mut orders: EventReader<OrderEvent>,
+    cargo: Query<&Cargo, With<Player>>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    let shaken = shocks.iter().any(|shock| {
+        shock.amount >= CLINK_SHOCK && cargo.get(shock.courier).is_ok_and(Cargo::is_fragile)
+    });
+    let handed_over = orders
+        .iter()
+        .any(|event| matches!(event, OrderEvent::PickedUp | OrderEvent::Delivered { .. }));
+    if broken.iter().count() > 0 {
+        sounds.send(PlaySound(Sound::Shatter));
+    } else if handed_over || shaken {
+        sounds.send(PlaySound(Sound::Clink));
+    }
+}
+
+fn play_sounds(
+    mut requests: EventReader<PlaySound>,
+    sounds: Res<Sounds>,
+    audio: Res<Audio<Synth>>,
+    volume: Res<Volume>,
+) {
+    for PlaySound(sound) in requests.iter() {
+        let speed = if sound.varies() {
+            1. + rand::random::<f32>() * 2. * PITCH_VARIATION - PITCH_VARIATION
+        } else {
+            1.
+        };
+        let settings = PlaybackSettings::ONCE
+            .with_volume(sound.gain() * volume.level(Channel::Effects))
+            .with_speed(speed);
+        audio.play_with_settings(sounds.effect(*sound), settings);
+    }
+}
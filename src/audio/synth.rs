@@ -0,0 +1,229 @@
+//! Sounds of the game, which are synthesized when the game starts instead of being loaded.
+
+use std::{f32::consts::TAU, sync::Arc, time::Duration};
+
+use bevy::{
+    audio::{Decodable, Source},
+    reflect::TypeUuid,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+pub const SAMPLE_RATE: u32 = 22050;
+
+/// Mono sound that is played by `Audio<Synth>`.
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "5b8d3c1e-9f0a-4d6b-8e2f-3a7c1b9d4e60"]
+pub struct Synth {
+    samples: Arc<[f32]>,
+}
+
+impl Synth {
+    fn new(samples: Vec<f32>) -> Self {
+        Self {
+            samples: samples.into(),
+        }
+    }
+}
+
+impl Decodable for Synth {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        SynthDecoder {
+            samples: self.samples.clone(),
+            position: 0,
+        }
+    }
+}
+
+pub struct SynthDecoder {
+    samples: Arc<[f32]>,
+    position: usize,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.samples.get(self.position).copied();
+        self.position += 1;
+        sample
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.position))
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.samples.len() as f32 / SAMPLE_RATE as f32,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Wave {
+    Sine,
+    Triangle,
+    Square,
+}
+
+impl Wave {
+    /// Value of the wave at provided phase, which is measured in periods.
+    fn at(&self, phase: f32) -> f32 {
+        let phase = phase.fract();
+        match self {
+            Wave::Sine => (phase * TAU).sin(),
+            Wave::Triangle => 1. - 4. * (phase - 0.5).abs(),
+            Wave::Square if phase < 0.5 => 1.,
+            Wave::Square => -1.,
+        }
+    }
+}
+
+/// Buffer that sounds are mixed into.
+struct Track(Vec<f32>);
+
+impl Track {
+    fn new(seconds: f32) -> Self {
+        Self(vec![0.; (seconds * SAMPLE_RATE as f32) as usize])
+    }
+
+    /// Adds a note that fades in quickly and decays over its duration.
+    fn note(&mut self, wave: Wave, frequency: f32, start: f32, duration: f32, volume: f32) {
+        let first = (start * SAMPLE_RATE as f32) as usize;
+        let length = (duration * SAMPLE_RATE as f32) as usize;
+        for i in 0..length {
+            let Some(sample) = self.0.get_mut(first + i) else {
+                break;
+            };
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let attack = (t / 0.01).min(1.);
+            let release = 1. - i as f32 / length as f32;
+            *sample += wave.at(frequency * t) * volume * attack * release * release;
+        }
+    }
+
+    /// Adds noise that is smoothed by low-pass filter with provided strength from 0 to 1.
+    fn noise(&mut self, rng: &mut ChaCha8Rng, start: f32, duration: f32, volume: f32, smooth: f32) {
+        let first = (start * SAMPLE_RATE as f32) as usize;
+        let length = (duration * SAMPLE_RATE as f32) as usize;
+        let mut filtered = 0.;
+        for i in 0..length {
+            let Some(sample) = self.0.get_mut(first + i) else {
+                break;
+            };
+            filtered += (rng.gen_range(-1.0..1.0) - filtered) * (1. - smooth);
+            let decay = (1. - i as f32 / length as f32).powi(3);
+            *sample += filtered * volume * decay;
+        }
+    }
+
+    fn finish(self) -> Synth {
+        Synth::new(self.0.into_iter().map(|s| s.clamp(-1., 1.)).collect())
+    }
+}
+
+/// Frequency of the note that is provided amount of semitones away from A4.
+fn pitch(semitones: i32) -> f32 {
+    440. * 2f32.powf(semitones as f32 / 12.)
+}
+
+/// Footstep on the surface with provided softness from 0 to 1.
+pub fn footstep(softness: f32) -> Synth {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let duration = 0.05 + 0.08 * softness;
+    let mut track = Track::new(duration);
+    track.noise(&mut rng, 0., duration, 0.6, 0.3 + 0.6 * softness);
+    track.note(Wave::Sine, 90. - 40. * softness, 0., duration, 0.3);
+    track.finish()
+}
+
+pub fn hover() -> Synth {
+    let mut track = Track::new(0.05);
+    track.note(Wave::Sine, pitch(12), 0., 0.05, 0.2);
+    track.finish()
+}
+
+pub fn click() -> Synth {
+    let mut track = Track::new(0.12);
+    track.note(Wave::Triangle, pitch(7), 0., 0.06, 0.3);
+    track.note(Wave::Triangle, pitch(14), 0.05, 0.07, 0.3);
+    track.finish()
+}
+
+/// Bottles touching each other.
+pub fn clink() -> Synth {
+    let mut track = Track::new(0.3);
+    for (frequency, volume) in [(2637., 0.2), (3729., 0.12), (5274., 0.08)] {
+        track.note(Wave::Sine, frequency, 0., 0.3, volume);
+    }
+    track.finish()
+}
+
+/// Bottles breaking.
+pub fn shatter() -> Synth {
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    let mut track = Track::new(0.6);
+    track.noise(&mut rng, 0., 0.4, 0.5, 0.1);
+    for _ in 0..8 {
+        let start = rng.gen_range(0.0..0.3);
+        let frequency = rng.gen_range(2000.0..6000.0);
+        track.note(Wave::Sine, frequency, start, 0.2, 0.1);
+    }
+    track.finish()
+}
+
+/// Calm loop of arpeggiated chords.
+pub fn menu_music() -> Synth {
+    const BEAT: f32 = 60. / 80.;
+    // Am, F, C and G.
+    let chords = [[0, 3, 7], [-4, 0, 3], [-9, -5, -2], [-2, 2, 5]];
+    let mut track = Track::new(chords.len() as f32 * 4. * BEAT);
+    for (bar, chord) in chords.iter().enumerate() {
+        let start = bar as f32 * 4. * BEAT;
+        track.note(Wave::Sine, pitch(chord[0] - 24), start, 4. * BEAT, 0.15);
+        for step in 0..8 {
+            let note = chord[step % 3] + if step >= 4 { 12 } else { 0 };
+            let time = start + step as f32 * BEAT / 2.;
+            track.note(Wave::Triangle, pitch(note), time, BEAT, 0.08);
+        }
+    }
+    track.finish()
+}
+
+/// Upbeat loop with a walking bass.
+pub fn game_music() -> Synth {
+    const BEAT: f32 = 60. / 120.;
+    // C, F, G and C.
+    let chords = [[-9, -5, -2], [-4, 0, 3], [-2, 2, 5], [-9, -5, -2]];
+    let mut track = Track::new(chords.len() as f32 * 4. * BEAT);
+    for (bar, chord) in chords.iter().enumerate() {
+        let start = bar as f32 * 4. * BEAT;
+        for beat in 0..4 {
+            let time = start + beat as f32 * BEAT;
+            let bass = chord[[0, 2, 1, 2][beat]] - 24;
+            track.note(Wave::Triangle, pitch(bass), time, BEAT, 0.2);
+            track.note(
+                Wave::Square,
+                pitch(chord[beat % 3]),
+                time + BEAT / 2.,
+                BEAT / 3.,
+                0.04,
+            );
+        }
+    }
+    track.finish()
+}
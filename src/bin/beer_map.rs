@@ -0,0 +1,431 @@
+//! Command-line tool used to generate, inspect and convert map files without running the game.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use beer_delivery::map::{
+    bounds::Location,
+    generator::CityGenerator,
+    preset,
+    tile::{DecorationTile, DoorTile, SolidTile, TileClass},
+    validation::{Diagnostic, ValidationRules},
+    MapData, MapLoadError, MapSaveError,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use image::{imageops, RgbaImage};
+use serde::{de::DeserializeOwned, Serialize};
+
+const TILE_SIZE: u32 = 16;
+
+#[derive(Parser)]
+#[command(about = "Generate, inspect and convert beer delivery maps")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate preset maps in the current directory.
+    GeneratePresets,
+    /// Generate random city with provided seed.
+    Generate {
+        seed: u64,
+        decoration: PathBuf,
+        solid: PathBuf,
+        /// Amount of blocks in every row of the city.
+        #[arg(long, default_value_t = 4)]
+        blocks_x: usize,
+        /// Amount of blocks in every column of the city.
+        #[arg(long, default_value_t = 4)]
+        blocks_y: usize,
+    },
+    /// Print size, spritesheet and tile histogram of the map.
+    Info {
+        path: PathBuf,
+        /// Layer that the map holds, which decides the kind of its tiles.
+        #[arg(long, value_enum)]
+        layer: Layer,
+    },
+    /// Convert map between formats. Files with `.ron` extension are stored as text.
+    Convert {
+        input: PathBuf,
+        output: PathBuf,
+        /// Layer that the map holds, which decides the kind of its tiles.
+        #[arg(long, value_enum)]
+        layer: Layer,
+    },
+    /// Check map for consistency with its spritesheet and other maps in the same directory.
+    Validate {
+        path: PathBuf,
+        /// Layer that the map holds, which decides the kind of its tiles.
+        #[arg(long, value_enum)]
+        layer: Layer,
+        /// Directory where spritesheets are located.
+        #[arg(long, default_value = "assets")]
+        assets: PathBuf,
+    },
+    /// Change size of the map, keeping its anchor in place.
+    Resize {
+        input: PathBuf,
+        output: PathBuf,
+        width: usize,
+        height: usize,
+        /// Layer that the map holds, which decides the kind of its tiles.
+        #[arg(long, value_enum)]
+        layer: Layer,
+        /// Side or corner of the map that stays in place.
+        #[arg(long, value_enum, default_value_t = Anchor::BottomLeft)]
+        anchor: Anchor,
+    },
+    /// Render map layers into PNG image.
+    Render {
+        output: PathBuf,
+        #[arg(long)]
+        decoration: Option<PathBuf>,
+        #[arg(long)]
+        door: Option<PathBuf>,
+        #[arg(long)]
+        solid: Option<PathBuf>,
+        /// Directory where spritesheets are located.
+        #[arg(long, default_value = "assets")]
+        assets: PathBuf,
+    },
+}
+
+/// Class of tiles stored in the map file.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Layer {
+    Decoration,
+    Solid,
+    Door,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Anchor {
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl From<Anchor> for Location {
+    fn from(anchor: Anchor) -> Self {
+        match anchor {
+            Anchor::Center => Location::Center,
+            Anchor::Top => Location::Top,
+            Anchor::Bottom => Location::Bottom,
+            Anchor::Left => Location::Left,
+            Anchor::Right => Location::Right,
+            Anchor::TopLeft => Location::TopLeft,
+            Anchor::TopRight => Location::TopRight,
+            Anchor::BottomLeft => Location::BottomLeft,
+            Anchor::BottomRight => Location::BottomRight,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ToolError {
+    #[error(transparent)]
+    Load(#[from] MapLoadError),
+    #[error(transparent)]
+    Save(#[from] MapSaveError),
+    #[error("couldn't process image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("{0} (is --layer right for this map?)")]
+    WrongLayer(MapLoadError),
+    #[error("spritesheet {0} is narrower than a tile")]
+    NarrowSpritesheet(String),
+    #[error("at least one layer is required")]
+    NoLayers,
+    #[error("map is invalid")]
+    Invalid,
+}
+
+/// Calls generic function with tile class that matches provided [Layer].
+macro_rules! with_layer {
+    ($layer:expr, $function:ident($($arg:expr),*)) => {
+        match $layer {
+            Layer::Decoration => $function::<DecorationTile>($($arg),*),
+            Layer::Solid => $function::<SolidTile>($($arg),*),
+            Layer::Door => $function::<DoorTile>($($arg),*),
+        }
+    };
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::GeneratePresets => preset::generate_preset_maps().map_err(ToolError::from),
+        Command::Generate {
+            seed,
+            decoration,
+            solid,
+            blocks_x,
+            blocks_y,
+        } => generate(seed, &decoration, &solid, blocks_x, blocks_y),
+        Command::Info { path, layer } => with_layer!(layer, info(&path)),
+        Command::Convert {
+            input,
+            output,
+            layer,
+        } => with_layer!(layer, convert(&input, &output)),
+        Command::Validate {
+            path,
+            layer,
+            assets,
+        } => with_layer!(layer, validate(&path, &assets)),
+        Command::Resize {
+            input,
+            output,
+            width,
+            height,
+            layer,
+            anchor,
+        } => with_layer!(layer, resize(&input, &output, width, height, anchor)),
+        Command::Render {
+            output,
+            decoration,
+            door,
+            solid,
+            assets,
+        } => render(
+            &output,
+            decoration.as_deref(),
+            door.as_deref(),
+            solid.as_deref(),
+            &assets,
+        ),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn generate(
+    seed: u64,
+    decoration: &Path,
+    solid: &Path,
+    blocks_x: usize,
+    blocks_y: usize,
+) -> Result<(), ToolError> {
+    let city = CityGenerator::new(seed)
+        .with_blocks(blocks_x, blocks_y)
+        .generate();
+    city.decoration.save(decoration)?;
+    city.solid.save(solid)?;
+    for point in city.delivery_points {
+        println!("{:?} at ({}, {})", point.kind, point.x, point.y);
+    }
+    Ok(())
+}
+
+/// Loads map of the layer, pointing to the layer as the likely culprit if it can't be decoded.
+fn load<T>(path: &Path) -> Result<MapData<T>, ToolError>
+where
+    T: TileClass + DeserializeOwned,
+{
+    MapData::<T>::load(path).map_err(|err| match err {
+        MapLoadError::DecodeError(_) | MapLoadError::TextDecodeError(_) => {
+            ToolError::WrongLayer(err)
+        }
+        err => err.into(),
+    })
+}
+
+fn info<T>(path: &Path) -> Result<(), ToolError>
+where
+    T: TileClass + DeserializeOwned,
+{
+    let map = load::<T>(path)?;
+    let size = map.size();
+    println!("size: {}x{}", size.x, size.y);
+    println!("spritesheet: {}", map.spritesheet());
+
+    let mut empty = 0;
+    let mut histogram = BTreeMap::<u32, usize>::new();
+    for (_, tile) in map.tiles() {
+        match tile {
+            Some(tile) => *histogram.entry(tile.texture()).or_default() += 1,
+            None => empty += 1,
+        }
+    }
+    println!("empty: {empty}");
+    for (texture, count) in histogram {
+        println!("texture {texture}: {count}");
+    }
+    Ok(())
+}
+
+fn convert<T>(input: &Path, output: &Path) -> Result<(), ToolError>
+where
+    T: TileClass + Serialize + DeserializeOwned,
+{
+    load::<T>(input)?.save(output)?;
+    Ok(())
+}
+
+fn validate<T>(path: &Path, assets: &Path) -> Result<(), ToolError>
+where
+    T: TileClass + DeserializeOwned,
+{
+    let map = match load::<T>(path) {
+        Ok(map) => map,
+        Err(ToolError::Load(MapLoadError::InvalidMap(diagnostics))) => {
+            diagnostics
+                .iter()
+                .for_each(|diagnostic| println!("{diagnostic}"));
+            return Err(ToolError::Invalid);
+        }
+        Err(err) => return Err(err),
+    };
+
+    let tileset = image::open(assets.join(map.spritesheet()))?;
+    let texture_count = (tileset.width() / TILE_SIZE) * (tileset.height() / TILE_SIZE);
+    let rules = ValidationRules::new()
+        .with_texture_count(texture_count)
+        .with_destinations(neighbour_maps(path)?);
+
+    let diagnostics = map.validate(&rules);
+    diagnostics
+        .iter()
+        .for_each(|diagnostic| println!("{diagnostic}"));
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        Err(ToolError::Invalid)
+    } else {
+        println!("map is valid");
+        Ok(())
+    }
+}
+
+/// Lists names of map files located in the same directory as provided map.
+///
+/// Doors of the map are expected to lead to one of them.
+fn neighbour_maps(path: &Path) -> Result<Vec<String>, ToolError> {
+    let directory = match path.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    };
+    let mut maps = Vec::new();
+    for entry in std::fs::read_dir(directory).map_err(MapLoadError::from)? {
+        let path = entry.map_err(MapLoadError::from)?.path();
+        let is_map = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("beer_map" | "ron")
+        );
+        if let (true, Some(name)) = (is_map, path.file_name().and_then(|name| name.to_str())) {
+            maps.push(name.to_owned());
+        }
+    }
+    Ok(maps)
+}
+
+fn resize<T>(
+    input: &Path,
+    output: &Path,
+    width: usize,
+    height: usize,
+    anchor: Anchor,
+) -> Result<(), ToolError>
+where
+    T: TileClass + Serialize + DeserializeOwned,
+{
+    let mut map = load::<T>(input)?;
+    map.resize(width, height, anchor.into());
+    map.save(output)?;
+    Ok(())
+}
+
+fn render(
+    output: &Path,
+    decoration: Option<&Path>,
+    door: Option<&Path>,
+    solid: Option<&Path>,
+    assets: &Path,
+) -> Result<(), ToolError> {
+    let decoration = decoration
+        .map(MapData::<DecorationTile>::load)
+        .transpose()?;
+    let door = door.map(MapData::<DoorTile>::load).transpose()?;
+    let solid = solid.map(MapData::<SolidTile>::load).transpose()?;
+
+    let sizes = [
+        decoration.as_ref().map(MapData::size),
+        door.as_ref().map(MapData::size),
+        solid.as_ref().map(MapData::size),
+    ];
+    let (width, height) = sizes
+        .into_iter()
+        .flatten()
+        .map(|size| (size.x, size.y))
+        .reduce(|a, b| (a.0.max(b.0), a.1.max(b.1)))
+        .ok_or(ToolError::NoLayers)?;
+
+    let mut canvas = RgbaImage::new(width * TILE_SIZE, height * TILE_SIZE);
+    if let Some(map) = &decoration {
+        draw_layer(&mut canvas, map, assets)?;
+    }
+    if let Some(map) = &door {
+        draw_layer(&mut canvas, map, assets)?;
+    }
+    if let Some(map) = &solid {
+        draw_layer(&mut canvas, map, assets)?;
+    }
+    canvas.save(output)?;
+    Ok(())
+}
+
+fn draw_layer<T>(canvas: &mut RgbaImage, map: &MapData<T>, assets: &Path) -> Result<(), ToolError>
+where
+    T: TileClass,
+{
+    let tileset = image::open(assets.join(map.spritesheet()))?.into_rgba8();
+    let columns = tileset.width() / TILE_SIZE;
+    let rows = tileset.height() / TILE_SIZE;
+    if columns == 0 {
+        return Err(ToolError::NarrowSpritesheet(map.spritesheet().to_owned()));
+    }
+    // Layers are aligned at the bottom left corner, which is the origin of the tilemap.
+    let height = canvas.height() / TILE_SIZE;
+
+    for ((x, y), tile) in map.tiles() {
+        let Some(tile) = tile else {
+            continue;
+        };
+        let (column, row) = (tile.texture() % columns, tile.texture() / columns);
+        if row >= rows {
+            continue;
+        }
+        let sprite = imageops::crop_imm(
+            &tileset,
+            column * TILE_SIZE,
+            row * TILE_SIZE,
+            TILE_SIZE,
+            TILE_SIZE,
+        )
+        .to_image();
+        // Tilemap rows go from bottom to top, while image rows go from top to bottom.
+        let image_y = (height - 1 - y as u32) * TILE_SIZE;
+        imageops::overlay(
+            canvas,
+            &sprite,
+            (x as u32 * TILE_SIZE) as i64,
+            image_y as i64,
+        );
+    }
+    Ok(())
+}
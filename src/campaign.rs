@@ -0,0 +1,267 @@
+//! Campaign of levels that are played one after another.
+//!
+//! [Campaign] is loaded from a RON file and consists of districts, each with a list of
+//! [Level]s. Level is complete once all of its [Objective]s are met before the closing time,
+//! and every extra objective that is met as well gives a star. Stars of the completed levels
+//! are kept in [Progress], which also decides what is unlocked.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Campaign {
+    /// Vehicles that are available from the start.
+    pub vehicles: Vec<Vehicle>,
+    /// Districts in the order they are played. The first one is unlocked from the start.
+    pub districts: Vec<District>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct District {
+    pub name: String,
+    /// Levels of the district, which are unlocked one by one.
+    pub levels: Vec<Level>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    /// Unique name of the level that the progress refers to.
+    pub id: String,
+    pub name: String,
+    pub map: LevelMap,
+    /// Hour of the first day at which the level ends.
+    pub closing_hour: u32,
+    /// Objectives that all have to be met to complete the level.
+    pub objectives: Vec<Objective>,
+    /// Objectives that give one star each, on top of the star for completing the level.
+    #[serde(default)]
+    pub stars: Vec<Objective>,
+    /// Content that is unlocked once the level is completed.
+    #[serde(default)]
+    pub unlocks: Vec<Unlock>,
+}
+
+/// Map that the level is played on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelMap {
+    /// Preset map stored on the disk.
+    Preset,
+    /// Procedurally generated city.
+    City {
+        seed: u64,
+        blocks_x: usize,
+        blocks_y: usize,
+    },
+}
+
+/// Requirement on the results of the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Objective {
+    /// Deliver at least this many orders.
+    Deliveries(u32),
+    /// Earn at least this many cents from deliveries and tips. Expenses, loans and fines
+    /// aren't counted.
+    Earnings(i64),
+    /// Cancel at most this many orders.
+    MaxCancellations(u32),
+    /// Break at most this many items.
+    MaxBroken(u32),
+}
+
+impl Objective {
+    pub fn holds(&self, stats: &LevelStats) -> bool {
+        match *self {
+            Objective::Deliveries(count) => stats.deliveries >= count,
+            Objective::Earnings(cents) => stats.earnings >= cents,
+            Objective::MaxCancellations(count) => stats.cancellations <= count,
+            Objective::MaxBroken(count) => stats.broken <= count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unlock {
+    /// District with provided name.
+    District(String),
+    Vehicle(Vehicle),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Vehicle {
+    Bike,
+    CargoBike,
+    Van,
+}
+
+/// Results of the level so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LevelStats {
+    pub deliveries: u32,
+    /// Deliveries that were made after the deadline.
+    #[serde(default)]
+    pub late: u32,
+    pub cancellations: u32,
+    pub broken: u32,
+    /// Income from the deliveries and tips in cents.
+    pub earnings: i64,
+}
+
+impl LevelStats {
+    /// Part of the deliveries that were made in time, which is 1 when nothing was delivered.
+    pub fn on_time_ratio(&self) -> f32 {
+        if self.deliveries == 0 {
+            1.
+        } else {
+            self.deliveries.saturating_sub(self.late) as f32 / self.deliveries as f32
+        }
+    }
+}
+
+impl Level {
+    pub fn is_complete(&self, stats: &LevelStats) -> bool {
+        self.objectives
+            .iter()
+            .all(|objective| objective.holds(stats))
+    }
+
+    /// Returns stars earned with provided results, which is zero for incomplete levels.
+    pub fn stars(&self, stats: &LevelStats) -> u32 {
+        if !self.is_complete(stats) {
+            return 0;
+        }
+        let extra = self.stars.iter().filter(|star| star.holds(stats)).count();
+        1 + extra as u32
+    }
+
+    pub fn max_stars(&self) -> u32 {
+        1 + self.stars.len() as u32
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CampaignLoadError {
+    #[error("couldn't load campaign file: {0}")]
+    FileSystemError(#[from] std::io::Error),
+    #[error("provided file is not in valid format: {0}")]
+    DecodeError(#[from] ron::error::SpannedError),
+    #[error("provided campaign is invalid: {}", .0[0])]
+    InvalidCampaign(Vec<CampaignError>),
+}
+
+/// Problem with the structure of the campaign.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CampaignError {
+    #[error("level \"{0}\" is defined more than once")]
+    DuplicateLevel(String),
+    #[error("level \"{level}\" unlocks district \"{district}\" that doesn't exist")]
+    UnknownDistrict { level: String, district: String },
+}
+
+impl Campaign {
+    /// Loads campaign and checks that it is valid.
+    pub fn load(path: &Path) -> Result<Self, CampaignLoadError> {
+        let bytes = std::fs::read(path)?;
+        let campaign: Campaign = ron::de::from_bytes(&bytes)?;
+        let errors = campaign.validate();
+        if !errors.is_empty() {
+            return Err(CampaignLoadError::InvalidCampaign(errors));
+        }
+        Ok(campaign)
+    }
+
+    /// Checks that level names are unique and unlocked districts exist.
+    pub fn validate(&self) -> Vec<CampaignError> {
+        let mut errors = Vec::new();
+        let mut ids = BTreeSet::new();
+        for level in self.levels() {
+            if !ids.insert(&level.id) {
+                errors.push(CampaignError::DuplicateLevel(level.id.clone()));
+            }
+            for unlock in &level.unlocks {
+                if let Unlock::District(district) = unlock {
+                    if !self.districts.iter().any(|d| &d.name == district) {
+                        errors.push(CampaignError::UnknownDistrict {
+                            level: level.id.clone(),
+                            district: district.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    pub fn levels(&self) -> impl Iterator<Item = &Level> {
+        self.districts.iter().flat_map(|district| &district.levels)
+    }
+
+    /// Returns district that the level with provided id belongs to.
+    pub fn district_of(&self, level: &str) -> Option<&District> {
+        self.districts
+            .iter()
+            .find(|district| district.levels.iter().any(|l| l.id == level))
+    }
+
+    /// Content unlocked by the completed levels.
+    fn unlocks<'campaign>(
+        &'campaign self,
+        progress: &'campaign Progress,
+    ) -> impl Iterator<Item = &'campaign Unlock> {
+        self.levels()
+            .filter(|level| progress.is_completed(&level.id))
+            .flat_map(|level| &level.unlocks)
+    }
+
+    pub fn is_district_unlocked(&self, progress: &Progress, district: usize) -> bool {
+        let Some(name) = self.districts.get(district).map(|district| &district.name) else {
+            return false;
+        };
+        district == 0
+            || self
+                .unlocks(progress)
+                .any(|unlock| matches!(unlock, Unlock::District(unlocked) if unlocked == name))
+    }
+
+    /// Level is unlocked once its district is and the level before it is completed.
+    pub fn is_level_unlocked(&self, progress: &Progress, district: usize, level: usize) -> bool {
+        if !self.is_district_unlocked(progress, district) {
+            return false;
+        }
+        let levels = &self.districts[district].levels;
+        match level {
+            0 => !levels.is_empty(),
+            _ if level < levels.len() => progress.is_completed(&levels[level - 1].id),
+            _ => false,
+        }
+    }
+
+    pub fn unlocked_vehicles(&self, progress: &Progress) -> BTreeSet<Vehicle> {
+        let unlocked = self.unlocks(progress).filter_map(|unlock| match unlock {
+            Unlock::Vehicle(vehicle) => Some(*vehicle),
+            Unlock::District(_) => None,
+        });
+        self.vehicles.iter().copied().chain(unlocked).collect()
+    }
+}
+
+/// Best amount of stars of every completed level.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Progress {
+    pub stars: BTreeMap<String, u32>,
+}
+
+impl Progress {
+    pub fn is_completed(&self, level: &str) -> bool {
+        self.stars.get(level).is_some_and(|&stars| stars > 0)
+    }
+
+    /// Records result of the level, keeping the best one.
+    pub fn record(&mut self, level: &str, stars: u32) {
+        let best = self.stars.entry(String::from(level)).or_default();
+        *best = (*best).max(stars);
+    }
+}
@@ -0,0 +1,177 @@
+//! Dialogues with the characters of the game.
+//!
+//! Dialogues are loaded from a RON file with a [DialogueSet]. Every [Dialogue] is a graph of
+//! [Node]s, where the courier moves to another node by picking one of the [Choice]s. Choices
+//! are only offered when their [Condition]s hold and may have [Effect]s on the game.
+
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// All dialogues of the game by their names.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DialogueSet {
+    pub dialogues: BTreeMap<String, Dialogue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dialogue {
+    /// Node that dialogue starts with.
+    pub start: String,
+    pub nodes: BTreeMap<String, Node>,
+}
+
+/// Single line of the dialogue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    pub speaker: String,
+    pub text: String,
+    /// Answers of the courier. Dialogue ends after the node if none of them is available.
+    #[serde(default)]
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Choice {
+    pub text: String,
+    /// Conditions that all have to hold for the choice to be offered.
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    /// Node that follows the choice. Dialogue ends if it's `None`.
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+/// Requirement on the state of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    /// Courier has at least this many cents.
+    MinCash(i64),
+    /// Courier has less than this many cents.
+    MaxCash(i64),
+    HasOrder,
+    NoOrder,
+    /// Beer of the active order is picked up.
+    PickedUp,
+    /// Place of the speaker is open.
+    Open,
+    /// Place of the speaker is closed, or the speaker has no place.
+    Closed,
+    /// Speaker waits for the active order.
+    Waiting,
+    /// Reputation with the speaker is at least this score.
+    MinReputation(f32),
+    /// Reputation with the speaker is below this score.
+    MaxReputation(f32),
+}
+
+impl Condition {
+    pub fn holds(&self, state: &DialogueState) -> bool {
+        match *self {
+            Condition::MinCash(cents) => state.cash >= cents,
+            Condition::MaxCash(cents) => state.cash < cents,
+            Condition::HasOrder => state.has_order,
+            Condition::NoOrder => !state.has_order,
+            Condition::PickedUp => state.picked_up,
+            Condition::Open => state.open,
+            Condition::Closed => !state.open,
+            Condition::Waiting => state.waiting,
+            Condition::MinReputation(score) => state.reputation >= score,
+            Condition::MaxReputation(score) => state.reputation < score,
+        }
+    }
+}
+
+/// Change to the game caused by the choice.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    /// Takes a new order from the brewery.
+    AcceptOrder,
+    /// Gives up the active order, which is fined.
+    CancelOrder,
+    /// Restores provided part of the speaker's patience.
+    Calm(f32),
+    /// Gives courier provided amount of cents, or takes them if it's negative.
+    Cash(i64),
+}
+
+/// State of the game that conditions are checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DialogueState {
+    /// Cash of the courier in cents.
+    pub cash: i64,
+    pub has_order: bool,
+    pub picked_up: bool,
+    pub open: bool,
+    pub waiting: bool,
+    /// Reputation with the speaker, or in the whole district for the brewery boss.
+    pub reputation: f32,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DialogueLoadError {
+    #[error("couldn't load dialogue file: {0}")]
+    FileSystemError(#[from] std::io::Error),
+    #[error("provided file is not in valid format: {0}")]
+    DecodeError(#[from] ron::error::SpannedError),
+    #[error("provided dialogues are invalid: {}", .0[0])]
+    InvalidDialogues(Vec<DialogueError>),
+}
+
+/// Problem with the dialogue graph.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DialogueError {
+    #[error("dialogue \"{dialogue}\" refers to node \"{node}\" that doesn't exist")]
+    MissingNode { dialogue: String, node: String },
+}
+
+impl DialogueSet {
+    /// Loads dialogues and checks that they are valid.
+    pub fn load(path: &Path) -> Result<Self, DialogueLoadError> {
+        let bytes = std::fs::read(path)?;
+        let set: DialogueSet = ron::de::from_bytes(&bytes)?;
+        let errors = set.validate();
+        if !errors.is_empty() {
+            return Err(DialogueLoadError::InvalidDialogues(errors));
+        }
+        Ok(set)
+    }
+
+    /// Checks that every node that dialogues refer to exists.
+    pub fn validate(&self) -> Vec<DialogueError> {
+        let mut errors = Vec::new();
+        for (name, dialogue) in &self.dialogues {
+            let references = dialogue
+                .nodes
+                .values()
+                .flat_map(|node| &node.choices)
+                .filter_map(|choice| choice.next.as_ref());
+            for node in std::iter::once(&dialogue.start).chain(references) {
+                if !dialogue.nodes.contains_key(node) {
+                    errors.push(DialogueError::MissingNode {
+                        dialogue: name.clone(),
+                        node: node.clone(),
+                    });
+                }
+            }
+        }
+        errors
+    }
+}
+
+impl Node {
+    /// Returns choices that are available in provided state.
+    pub fn available_choices<'node>(
+        &'node self,
+        state: &'node DialogueState,
+    ) -> impl Iterator<Item = &'node Choice> {
+        self.choices.iter().filter(|choice| {
+            choice
+                .conditions
+                .iter()
+                .all(|condition| condition.holds(state))
+        })
+    }
+}
@@ -1,18 +1,87 @@
+pub mod arcade;
+pub mod camera;
+pub mod cargo;
+pub mod clock;
+pub mod customer;
+pub mod depth;
+pub mod dialogue;
+pub mod economy;
+pub mod hud;
+pub mod input;
+pub mod interaction;
+pub mod level;
 pub mod map;
+pub mod minimap;
+pub mod navigation;
+pub mod order;
 pub mod player;
+pub mod replay;
+pub mod reputation;
+pub mod results;
+pub mod simulation;
+pub mod traffic;
+pub mod vehicle;
 
-use bevy::prelude::*;
+use bevy::{app::PluginGroupBuilder, prelude::*};
 
 use crate::GameState;
 
+/// Whole game, including everything that is shown and heard.
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(player::PlayerPlugin)
-            .add_plugin(map::MapPlugin)
-            .configure_set(GameSystemSet.run_if(in_state(GameState::Game)))
-            .add_system(game_setup.in_schedule(OnEnter(GameState::Game)))
+        app.add_plugins(GameplayPlugins)
+            .add_plugin(input::DeviceInputPlugin)
+            .add_plugin(replay::ReplayPlugin)
+            .add_plugin(player::CourierSpritePlugin)
+            .add_plugin(camera::CameraPlugin)
+            .add_plugin(map::TilemapRenderPlugin)
+            .add_plugin(navigation::GpsArrowPlugin)
+            .add_plugin(depth::DepthPlugin)
+            .add_plugin(customer::CustomerPlugin)
+            .add_plugin(dialogue::DialoguePlugin)
+            .add_plugin(vehicle::VehiclePlugin)
+            .add_plugin(traffic::TrafficPlugin)
+            .add_plugin(hud::HudPlugin)
+            .add_plugin(minimap::MinimapPlugin)
+            .add_plugin(level::LevelPlugin)
+            .add_plugin(reputation::ReputationPlugin)
+            .add_plugin(results::ResultsPlugin)
+            .add_plugin(arcade::ArcadePlugin);
+    }
+}
+
+/// Core of the gameplay that runs without a window: the map, couriers walking through it
+/// according to [StepInput](input::StepInput), and the orders.
+///
+/// Together with [MinimalPlugins] and [GameState] it makes a headless simulation of the game,
+/// which is what the tests play.
+pub struct GameplayPlugins;
+
+impl PluginGroup for GameplayPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(SessionPlugin)
+            .add(simulation::SimulationPlugin)
+            .add(input::InputPlugin)
+            .add(player::PlayerPlugin)
+            .add(map::MapPlugin)
+            .add(navigation::NavigationPlugin)
+            .add(cargo::CargoPlugin)
+            .add(economy::EconomyPlugin)
+            .add(interaction::InteractionPlugin)
+            .add(clock::ClockPlugin)
+            .add(order::OrderPlugin)
+    }
+}
+
+/// Runs the game systems during the game and cleans up after it.
+struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_set(GameSystemSet.run_if(in_state(GameState::Game)))
             .add_system(game_cleanup.in_schedule(OnExit(GameState::Game)));
     }
 }
@@ -20,8 +89,6 @@ impl Plugin for GamePlugin {
 #[derive(SystemSet, Debug, Clone, Copy, Hash, PartialEq, Eq)]
 struct GameSystemSet;
 
-fn game_setup(mut _commands: Commands, _asset_server: Res<AssetServer>) {}
-
 fn game_cleanup(entities: Query<Entity, With<OnGameScreen>>, mut commands: Commands) {
     entities.for_each(|entity| commands.entity(entity).despawn_recursive());
 }
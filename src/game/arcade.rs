@@ -0,0 +1,169 @@
+//! Timed arcade mode.
+//!
+//! Arcade shift lasts [SHIFT_LENGTH] of real time and the goal is to earn as much as possible.
+//! Couriers don't have to ask for orders, a new one comes on its own a moment after the
+//! previous one of the team is done, and these moments get shorter as the shift goes on.
+
+use bevy::prelude::*;
+
+use crate::{menu::FONT, GameState};
+
+use super::{
+    order::{ActiveOrders, OrderRequest},
+    simulation::SimulationSystems,
+    GameSystemSet, OnGameScreen,
+};
+
+/// Real time that the arcade shift lasts, in seconds.
+pub const SHIFT_LENGTH: f32 = 300.;
+/// Time between the orders at the start of the shift.
+const FIRST_INTERVAL: f32 = 15.;
+/// Time between the orders at the end of the shift.
+const LAST_INTERVAL: f32 = 2.;
+const FONT_SIZE: f32 = 24.;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HURRY_COLOR: Color = Color::rgb(1.0, 0.4, 0.3);
+/// Time left at which the timer turns red.
+const HURRY_TIME: f32 = 30.;
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+pub struct ArcadePlugin;
+
+impl Plugin for ArcadePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Arcade>()
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_system(cleanup.in_schedule(OnExit(GameState::Game)))
+            .add_step_system(send_orders.run_if(resource_exists::<ArcadeRun>()))
+            .add_system(
+                update_timer
+                    .in_set(GameSystemSet)
+                    .run_if(resource_exists::<ArcadeRun>()),
+            );
+    }
+}
+
+/// Whether the next game is an arcade shift, which is chosen in the menu.
+#[derive(Resource, Debug, Default)]
+pub struct Arcade(pub bool);
+
+/// Progress of the arcade shift that is played.
+#[derive(Resource, Debug)]
+pub struct ArcadeRun {
+    /// Real time since the start of the shift.
+    elapsed: f32,
+    /// Time left until the next order of every team comes.
+    next_order: Vec<f32>,
+}
+
+impl ArcadeRun {
+    pub fn time_left(&self) -> f32 {
+        (SHIFT_LENGTH - self.elapsed).max(0.)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.elapsed >= SHIFT_LENGTH
+    }
+
+    /// Time between the orders at this point of the shift.
+    fn interval(&self) -> f32 {
+        let progress = (self.elapsed / SHIFT_LENGTH).min(1.);
+        FIRST_INTERVAL + (LAST_INTERVAL - FIRST_INTERVAL) * progress
+    }
+}
+
+/// Time left until the end of the shift.
+#[derive(Component)]
+struct TimerText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, arcade: Res<Arcade>) {
+    if !arcade.0 {
+        return;
+    }
+    commands.insert_resource(ArcadeRun {
+        elapsed: 0.,
+        next_order: Vec::new(),
+    });
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(0.),
+                        right: Val::Px(0.),
+                        top: Val::Px(10.),
+                        ..default()
+                    },
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(8.)),
+                        ..default()
+                    },
+                    background_color: PANEL_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let style = TextStyle {
+                        font: asset_server.load(FONT),
+                        font_size: FONT_SIZE,
+                        color: TEXT_COLOR,
+                    };
+                    parent.spawn((TextBundle::from_section("", style), TimerText));
+                });
+        });
+}
+
+fn cleanup(mut commands: Commands) {
+    commands.remove_resource::<ArcadeRun>();
+}
+
+/// Requests the next order of every team once it has been free for long enough.
+fn send_orders(
+    time: Res<Time>,
+    active: Res<ActiveOrders>,
+    mut run: ResMut<ArcadeRun>,
+    mut requests: EventWriter<OrderRequest>,
+) {
+    if run.is_over() {
+        return;
+    }
+    run.elapsed += time.delta_seconds();
+    run.next_order.resize(active.0.len(), FIRST_INTERVAL);
+    let interval = run.interval();
+    for (team, next_order) in run.next_order.iter_mut().enumerate() {
+        if active.get(team).is_some() {
+            continue;
+        }
+        *next_order -= time.delta_seconds();
+        if *next_order <= 0. {
+            *next_order = interval;
+            requests.send(OrderRequest::New(team));
+        }
+    }
+}
+
+fn update_timer(run: Res<ArcadeRun>, mut text: Query<&mut Text, With<TimerText>>) {
+    let time_left = run.time_left().ceil() as u32;
+    for mut text in &mut text {
+        text.sections[0].value = if run.is_over() {
+            String::from("Time's up!")
+        } else {
+            format!("Arcade, time left {}:{:02}", time_left / 60, time_left % 60)
+        };
+        text.sections[0].style.color = if run.time_left() <= HURRY_TIME {
+            HURRY_COLOR
+        } else {
+            TEXT_COLOR
+        };
+    }
+}
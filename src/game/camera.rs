@@ -0,0 +1,122 @@
+//! Cameras that follow the couriers.
+//!
+//! Single courier is followed by the main camera. In co-op every courier gets a camera of its
+//! own that renders to its part of the window, while the main camera only renders the
+//! interface on top of them.
+
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{camera::Viewport, view::RenderLayers},
+    window::PrimaryWindow,
+};
+
+use crate::GameState;
+
+use super::{player::Coop, GameSystemSet, OnGameScreen};
+
+/// Render layer without any entities, so that the main camera only renders the interface.
+const INTERFACE_LAYER: u8 = RenderLayers::TOTAL_LAYERS as u8 - 1;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_system(cleanup.in_schedule(OnExit(GameState::Game)))
+            .add_system(update_viewports.in_set(GameSystemSet));
+    }
+}
+
+/// Camera that follows the courier with provided index.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerCamera(pub usize);
+
+fn setup(
+    mut commands: Commands,
+    coop: Res<Coop>,
+    mut main: Query<
+        (Entity, &mut Camera2d, &OrthographicProjection),
+        (With<Camera>, Without<PlayerCamera>),
+    >,
+) {
+    let Ok((entity, mut camera_2d, projection)) = main.get_single_mut() else {
+        return;
+    };
+    let players = coop.players();
+    if players == 1 {
+        commands.entity(entity).insert(PlayerCamera(0));
+        return;
+    }
+
+    camera_2d.clear_color = ClearColorConfig::None;
+    commands
+        .entity(entity)
+        .insert(RenderLayers::layer(INTERFACE_LAYER));
+    for index in 0..players {
+        commands.spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    // Below the main camera, which renders the interface.
+                    order: index as isize - players as isize,
+                    ..default()
+                },
+                camera_2d: Camera2d {
+                    // Clearing happens for the whole window, so only the first camera does it.
+                    clear_color: if index == 0 {
+                        ClearColorConfig::Default
+                    } else {
+                        ClearColorConfig::None
+                    },
+                },
+                projection: projection.clone(),
+                ..default()
+            },
+            UiCameraConfig { show_ui: false },
+            PlayerCamera(index),
+            OnGameScreen,
+        ));
+    }
+}
+
+fn cleanup(
+    mut commands: Commands,
+    mut main: Query<(Entity, &mut Camera2d), (With<Camera>, Without<OnGameScreen>)>,
+) {
+    for (entity, mut camera_2d) in &mut main {
+        camera_2d.clear_color = ClearColorConfig::Default;
+        commands
+            .entity(entity)
+            .remove::<(PlayerCamera, RenderLayers)>();
+    }
+}
+
+/// Splits the window between the couriers' cameras side by side.
+fn update_viewports(
+    coop: Res<Coop>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&mut Camera, &PlayerCamera)>,
+) {
+    let players = coop.players() as u32;
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let size = UVec2::new(window.physical_width() / players, window.physical_height());
+    if players == 1 || size.x == 0 || size.y == 0 {
+        return;
+    }
+    for (mut camera, PlayerCamera(index)) in &mut cameras {
+        let position = UVec2::new(*index as u32 * size.x, 0);
+        let current = camera
+            .viewport
+            .as_ref()
+            .map(|viewport| (viewport.physical_position, viewport.physical_size));
+        if current != Some((position, size)) {
+            camera.viewport = Some(Viewport {
+                physical_position: position,
+                physical_size: size,
+                ..default()
+            });
+        }
+    }
+}
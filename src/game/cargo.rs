@@ -0,0 +1,324 @@
+//! Beer carried by the courier.
+//!
+//! Movement of the courier sends [Shock] events, which put strain on fragile items and
+//! eventually break them.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use bevy::prelude::*;
+
+use super::{
+    economy::Money,
+    player::Player,
+    simulation::{SimulationEvents, SimulationSystems},
+    vehicle::VehicleKind,
+    GameSystemSet,
+};
+
+/// Part of the speed that is lost when carrier is fully loaded.
+const MAX_SLOWDOWN: f32 = 0.5;
+/// Color of the courier right after something broke.
+const BREAK_COLOR: Color = Color::rgb(1.0, 0.4, 0.4);
+/// Time for which courier stays highlighted after something broke.
+const BREAK_FLASH_TIME: f32 = 0.3;
+
+pub struct CargoPlugin;
+
+impl Plugin for CargoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<Shock>()
+            .add_simulation_event::<CargoBroken>()
+            .add_step_systems((apply_shocks, flash_on_break))
+            .add_system(fade_flash.in_set(GameSystemSet));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BeerItem {
+    Keg,
+    Crate,
+    Bottle,
+}
+
+impl BeerItem {
+    /// Weight of one item in kilograms.
+    pub fn weight(&self) -> f32 {
+        match self {
+            BeerItem::Keg => 50.,
+            BeerItem::Crate => 12.,
+            BeerItem::Bottle => 0.5,
+        }
+    }
+
+    /// Part of the shock that is applied to the item, from 0 for unbreakable items to 1.
+    pub fn fragility(&self) -> f32 {
+        match self {
+            BeerItem::Keg => 0.,
+            BeerItem::Crate => 0.3,
+            BeerItem::Bottle => 1.,
+        }
+    }
+
+    /// Price that recipient pays for one item.
+    pub fn price(&self) -> Money {
+        match self {
+            BeerItem::Keg => Money::dollars(8),
+            BeerItem::Crate => Money::dollars(3),
+            BeerItem::Bottle => Money::cents(40),
+        }
+    }
+
+    pub fn name(&self, count: u32) -> &'static str {
+        match (self, count) {
+            (BeerItem::Keg, 1) => "keg",
+            (BeerItem::Keg, _) => "kegs",
+            (BeerItem::Crate, 1) => "crate",
+            (BeerItem::Crate, _) => "crates",
+            (BeerItem::Bottle, 1) => "bottle",
+            (BeerItem::Bottle, _) => "bottles",
+        }
+    }
+}
+
+/// Way the cargo is carried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Carrier {
+    #[default]
+    OnFoot,
+    HandTruck,
+    Vehicle(VehicleKind),
+}
+
+impl Carrier {
+    /// Carriers available to the courier on foot, from the smallest one to the largest.
+    pub const ON_FOOT: [Carrier; 2] = [Carrier::OnFoot, Carrier::HandTruck];
+
+    /// Maximal weight of the cargo in kilograms.
+    pub fn capacity(&self) -> f32 {
+        match self {
+            Carrier::OnFoot => 15.,
+            Carrier::HandTruck => 120.,
+            Carrier::Vehicle(kind) => kind.capacity(),
+        }
+    }
+
+    /// Speed relative to walking without any cargo.
+    ///
+    /// Vehicles have their own speed, so for them it is always 1.
+    pub fn speed(&self) -> f32 {
+        match self {
+            Carrier::OnFoot => 1.,
+            Carrier::HandTruck => 0.9,
+            Carrier::Vehicle(_) => 1.,
+        }
+    }
+
+    pub fn is_vehicle(&self) -> bool {
+        matches!(self, Carrier::Vehicle(_))
+    }
+
+    /// Returns the smallest carrier available on foot that is able to hold provided weight.
+    pub fn for_weight(weight: f32) -> Option<Carrier> {
+        Self::ON_FOOT
+            .into_iter()
+            .find(|carrier| carrier.capacity() >= weight)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Carrier::OnFoot => "on foot",
+            Carrier::HandTruck => "hand truck",
+            Carrier::Vehicle(kind) => kind.name(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum CargoError {
+    #[error("cargo is too heavy for the carrier")]
+    TooHeavy,
+}
+
+/// Beer carried by the courier.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Cargo {
+    items: BTreeMap<BeerItem, u32>,
+    /// Accumulated strain of every item type. One item breaks for every whole unit of strain.
+    strain: BTreeMap<BeerItem, f32>,
+    carrier: Carrier,
+}
+
+impl Cargo {
+    pub fn carrier(&self) -> Carrier {
+        self.carrier
+    }
+
+    /// Changes the carrier, as long as it is able to hold current cargo.
+    pub fn set_carrier(&mut self, carrier: Carrier) -> Result<(), CargoError> {
+        if self.weight() > carrier.capacity() {
+            return Err(CargoError::TooHeavy);
+        }
+        self.carrier = carrier;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Whether any of the carried items is able to break.
+    pub fn is_fragile(&self) -> bool {
+        self.items.keys().any(|item| item.fragility() > 0.)
+    }
+
+    /// Total weight of the cargo in kilograms.
+    pub fn weight(&self) -> f32 {
+        self.items
+            .iter()
+            .map(|(item, &count)| item.weight() * count as f32)
+            .sum()
+    }
+
+    pub fn load(&mut self, item: BeerItem, count: u32) -> Result<(), CargoError> {
+        if self.weight() + item.weight() * count as f32 > self.carrier.capacity() {
+            return Err(CargoError::TooHeavy);
+        }
+        *self.items.entry(item).or_default() += count;
+        Ok(())
+    }
+
+    /// Unloads up to `count` items and returns how many were actually unloaded.
+    pub fn unload(&mut self, item: BeerItem, count: u32) -> u32 {
+        let Some(carried) = self.items.get_mut(&item) else {
+            return 0;
+        };
+        let unloaded = count.min(*carried);
+        *carried -= unloaded;
+        if *carried == 0 {
+            self.items.remove(&item);
+            self.strain.remove(&item);
+        }
+        unloaded
+    }
+
+    /// Applies shock to the cargo and returns items that broke because of it.
+    pub fn shake(&mut self, shock: f32) -> Vec<(BeerItem, u32)> {
+        let mut broken = Vec::new();
+        for (&item, carried) in self.items.iter_mut() {
+            let strain = self.strain.entry(item).or_default();
+            *strain += shock * item.fragility();
+            let count = (strain.floor() as u32).min(*carried);
+            if count > 0 {
+                *strain -= count as f32;
+                *carried -= count;
+                broken.push((item, count));
+            }
+        }
+        self.items.retain(|_, &mut carried| carried > 0);
+        self.strain.retain(|item, _| self.items.contains_key(item));
+        broken
+    }
+
+    /// Speed relative to walking without any cargo.
+    pub fn speed(&self) -> f32 {
+        let load = self.weight() / self.carrier.capacity();
+        self.carrier.speed() * (1. - MAX_SLOWDOWN * load.min(1.))
+    }
+}
+
+impl Display for Cargo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "empty");
+        }
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .map(|(item, &count)| format!("{count} {}", item.name(count)))
+            .collect();
+        write!(
+            f,
+            "{} ({:.0}/{:.0} kg, {})",
+            items.join(", "),
+            self.weight(),
+            self.carrier.capacity(),
+            self.carrier.name()
+        )
+    }
+}
+
+/// Sudden change of the courier's movement, such as collision or abrupt stop.
+///
+/// Strength of 1 is enough to break a bottle.
+#[derive(Debug, Clone, Copy)]
+pub struct Shock {
+    pub courier: Entity,
+    pub amount: f32,
+}
+
+/// Sent when some of the cargo breaks.
+#[derive(Debug, Clone, Copy)]
+pub struct CargoBroken {
+    /// Courier that carried the cargo.
+    pub courier: Entity,
+    pub item: BeerItem,
+    pub count: u32,
+}
+
+#[derive(Component, Deref, DerefMut)]
+struct BreakFlash(Timer);
+
+fn apply_shocks(
+    mut shocks: EventReader<Shock>,
+    mut cargo: Query<(Entity, &mut Cargo), With<Player>>,
+    mut broken: EventWriter<CargoBroken>,
+) {
+    let shocks: Vec<_> = shocks.iter().collect();
+    for (courier, mut cargo) in &mut cargo {
+        let shock: f32 = shocks
+            .iter()
+            .filter(|shock| shock.courier == courier)
+            .map(|shock| shock.amount)
+            .sum();
+        if shock <= 0. || cargo.is_empty() {
+            continue;
+        }
+        for (item, count) in cargo.shake(shock) {
+            broken.send(CargoBroken {
+                courier,
+                item,
+                count,
+            });
+        }
+    }
+}
+
+fn flash_on_break(
+    mut broken: EventReader<CargoBroken>,
+    mut player: Query<&mut TextureAtlasSprite, With<Player>>,
+    mut commands: Commands,
+) {
+    for &CargoBroken { courier, .. } in broken.iter() {
+        let Ok(mut sprite) = player.get_mut(courier) else {
+            continue;
+        };
+        sprite.color = BREAK_COLOR;
+        commands.entity(courier).insert(BreakFlash(Timer::from_seconds(
+            BREAK_FLASH_TIME,
+            TimerMode::Once,
+        )));
+    }
+}
+
+fn fade_flash(
+    time: Res<Time>,
+    mut flashing: Query<(Entity, &mut BreakFlash, &mut TextureAtlasSprite)>,
+    mut commands: Commands,
+) {
+    for (entity, mut flash, mut sprite) in &mut flashing {
+        if flash.tick(time.delta()).finished() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<BreakFlash>();
+        }
+    }
+}
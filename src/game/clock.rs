@@ -0,0 +1,230 @@
+//! In-game time of day.
+//!
+//! [GameClock] runs faster than real time, tints the screen according to the time of day
+//! and decides which delivery points are open. Every midnight [DayEnded] is sent with the
+//! summary of the day.
+
+use std::fmt::Display;
+
+use bevy::prelude::*;
+
+use crate::{map::delivery::DeliveryPointKind, GameState};
+
+use super::{
+    economy::{CashChanged, Money},
+    order::OrderEvent,
+    simulation::{SimulationEvents, SimulationSystems},
+    GameSystemSet, OnGameScreen,
+};
+
+/// Default speed of the in-game time.
+const MINUTES_PER_SECOND: f32 = 2.;
+const MINUTES_PER_DAY: f32 = 24. * 60.;
+/// Time of the day at which the game starts, in minutes.
+const START_TIME: f32 = 8. * 60.;
+/// Screen tint at the given hour. Tint between the keyframes is interpolated.
+const TINT: [(f32, Color); 8] = [
+    (0., Color::rgba(0.02, 0.02, 0.15, 0.6)),
+    (5., Color::rgba(0.02, 0.02, 0.15, 0.6)),
+    (7., Color::rgba(1.0, 0.8, 0.5, 0.)),
+    (17., Color::rgba(1.0, 0.8, 0.5, 0.)),
+    (19., Color::rgba(0.9, 0.4, 0.2, 0.2)),
+    (21., Color::rgba(0.02, 0.02, 0.15, 0.5)),
+    (22., Color::rgba(0.02, 0.02, 0.15, 0.6)),
+    (24., Color::rgba(0.02, 0.02, 0.15, 0.6)),
+];
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>()
+            .init_resource::<DaySummary>()
+            .add_simulation_event::<DayEnded>()
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_step_systems((record_orders, record_cash, tick))
+            .add_system(update_tint.in_set(GameSystemSet));
+    }
+}
+
+/// Time of the day and number of the current day.
+#[derive(Resource, Debug, Clone)]
+pub struct GameClock {
+    /// Minutes since the midnight.
+    minutes: f32,
+    /// Number of the current day, starting from 1.
+    day: u32,
+    /// In-game minutes that pass during one real second.
+    pub minutes_per_second: f32,
+}
+
+impl GameClock {
+    /// Current hour including fractional part.
+    pub fn hour(&self) -> f32 {
+        self.minutes / 60.
+    }
+
+    /// Number of the current day, starting from 1.
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// Checks whether delivery point of provided kind is open right now.
+    pub fn is_open(&self, kind: DeliveryPointKind) -> bool {
+        let (open, close) = opening_hours(kind);
+        let hour = self.hour() as u32;
+        if open <= close {
+            (open..close).contains(&hour)
+        } else {
+            // Opening hours go past midnight.
+            hour >= open || hour < close
+        }
+    }
+
+    fn tint(&self) -> Color {
+        let hour = self.hour();
+        let next = TINT
+            .iter()
+            .position(|&(keyframe, _)| keyframe > hour)
+            .unwrap_or(TINT.len() - 1)
+            .max(1);
+        let (from, from_color) = TINT[next - 1];
+        let (to, to_color) = TINT[next];
+        let t = ((hour - from) / (to - from)).clamp(0., 1.);
+        let [r1, g1, b1, a1] = from_color.as_rgba_f32();
+        let [r2, g2, b2, a2] = to_color.as_rgba_f32();
+        Color::rgba(
+            r1 + (r2 - r1) * t,
+            g1 + (g2 - g1) * t,
+            b1 + (b2 - b1) * t,
+            a1 + (a2 - a1) * t,
+        )
+    }
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self {
+            minutes: START_TIME,
+            day: 1,
+            minutes_per_second: MINUTES_PER_SECOND,
+        }
+    }
+}
+
+impl Display for GameClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let minutes = self.minutes as u32;
+        write!(
+            f,
+            "Day {} {:02}:{:02}",
+            self.day,
+            minutes / 60,
+            minutes % 60
+        )
+    }
+}
+
+/// Hours during which delivery point accepts orders. Closing hour may be past midnight.
+pub fn opening_hours(kind: DeliveryPointKind) -> (u32, u32) {
+    match kind {
+        DeliveryPointKind::Brewery => (6, 22),
+        DeliveryPointKind::Bar => (16, 2),
+        DeliveryPointKind::Customer => (9, 23),
+    }
+}
+
+/// Results of the day that are collected until the midnight.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct DaySummary {
+    pub day: u32,
+    pub deliveries: u32,
+    pub cancellations: u32,
+    pub income: Money,
+    pub expenses: Money,
+}
+
+impl DaySummary {
+    pub fn profit(&self) -> Money {
+        self.income - self.expenses
+    }
+}
+
+/// Sent at the midnight with the summary of the day that has ended.
+#[derive(Debug, Clone)]
+pub struct DayEnded(pub DaySummary);
+
+/// Screen-wide overlay that tints the city according to the time of day.
+#[derive(Component)]
+struct Tint;
+
+fn setup(mut commands: Commands, mut clock: ResMut<GameClock>) {
+    // Speed of the clock is a setting, so it persists between games.
+    *clock = GameClock {
+        minutes_per_second: clock.minutes_per_second,
+        ..default()
+    };
+    commands.insert_resource(DaySummary {
+        day: 1,
+        ..default()
+    });
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            // Below the rest of the interface.
+            z_index: ZIndex::Global(-1),
+            ..default()
+        },
+        Tint,
+        OnGameScreen,
+    ));
+}
+
+fn tick(
+    time: Res<Time>,
+    mut clock: ResMut<GameClock>,
+    mut summary: ResMut<DaySummary>,
+    mut day_ended: EventWriter<DayEnded>,
+) {
+    clock.minutes += time.delta_seconds() * clock.minutes_per_second;
+    while clock.minutes >= MINUTES_PER_DAY {
+        clock.minutes -= MINUTES_PER_DAY;
+        clock.day += 1;
+        let next = DaySummary {
+            day: clock.day,
+            ..default()
+        };
+        day_ended.send(DayEnded(std::mem::replace(&mut *summary, next)));
+    }
+}
+
+fn record_orders(mut events: EventReader<OrderEvent>, mut summary: ResMut<DaySummary>) {
+    for event in events.iter() {
+        match event {
+            OrderEvent::Delivered { .. } => summary.deliveries += 1,
+            OrderEvent::Cancelled { .. } => summary.cancellations += 1,
+            OrderEvent::Accepted | OrderEvent::PickedUp => {}
+        }
+    }
+}
+
+fn record_cash(mut cash_changed: EventReader<CashChanged>, mut summary: ResMut<DaySummary>) {
+    for CashChanged { transaction, .. } in cash_changed.iter() {
+        if transaction.amount.is_negative() {
+            summary.expenses += -transaction.amount;
+        } else {
+            summary.income += transaction.amount;
+        }
+    }
+}
+
+fn update_tint(clock: Res<GameClock>, mut tint: Query<&mut BackgroundColor, With<Tint>>) {
+    for mut background in &mut tint {
+        background.0 = clock.tint();
+    }
+}
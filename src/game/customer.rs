@@ -0,0 +1,318 @@
+//! Customers waiting for the beer at bars and houses.
+//!
+//! Every delivery point except the brewery has a customer standing at the door. Customer
+//! loses patience while waiting for the order, and the mood it ends up in decides the tip
+//! together with the reputation of the courier.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    map::{
+        delivery::{DeliveryPoint, DeliveryPointKind},
+        tile::DecorationTile,
+        MapData,
+    },
+    menu::FONT,
+    reputation,
+};
+
+use super::{
+    depth::{self, YSort},
+    dialogue::Speaker,
+    economy::{Money, Transaction, TransactionKind},
+    interaction::Interactable,
+    map::{tile_to_world, TILE_SIZE},
+    order::{ActiveOrders, OrderEvent},
+    player::{Direction, MovementAnimation, CHARACTER_COUNT, CHARACTER_STRIDE},
+    reputation::Standing,
+    simulation::{GameRng, SimulationSystems},
+    GameSystemSet, OnGameScreen,
+};
+
+/// Time for which customer is able to wait before losing all the patience.
+const PATIENCE_TIME: f32 = 90.;
+/// Time that it takes for customer to fully calm down after waiting.
+const RECOVERY_TIME: f32 = 30.;
+/// Time for which reaction stays above the customer.
+const REACTION_TIME: f32 = 2.;
+/// Speed at which reaction floats up.
+const REACTION_SPEED: f32 = 6.;
+
+pub struct CustomerPlugin;
+
+impl Plugin for CustomerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_step_systems((spawn_customers, wait, react))
+            .add_systems((animate_customers, float_reactions).in_set(GameSystemSet));
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Customer {
+    /// Delivery point that customer waits at.
+    pub point: DeliveryPoint,
+    /// Patience left, from 1 for a calm customer to 0 for an out of patience one.
+    patience: f32,
+}
+
+impl Customer {
+    pub fn new(point: DeliveryPoint) -> Self {
+        Self {
+            point,
+            patience: 1.,
+        }
+    }
+
+    /// Restores provided part of the patience.
+    pub fn calm(&mut self, amount: f32) {
+        self.patience = (self.patience + amount).min(1.);
+    }
+
+    pub fn mood(&self) -> Mood {
+        match self.patience {
+            p if p > 0.66 => Mood::Happy,
+            p if p > 0.33 => Mood::Neutral,
+            p if p > 0. => Mood::Annoyed,
+            _ => Mood::Furious,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mood {
+    Happy,
+    Neutral,
+    Annoyed,
+    Furious,
+}
+
+impl Mood {
+    /// Part of the payout that customer adds as a tip.
+    pub fn tip_share(&self) -> f32 {
+        match self {
+            Mood::Happy => 0.2,
+            Mood::Neutral => 0.1,
+            Mood::Annoyed | Mood::Furious => 0.,
+        }
+    }
+
+    /// Time between frames of the idle animation. Impatient customers fidget faster.
+    fn frame_time(&self) -> f32 {
+        match self {
+            Mood::Happy => 1.2,
+            Mood::Neutral => 0.8,
+            Mood::Annoyed => 0.4,
+            Mood::Furious => 0.2,
+        }
+    }
+}
+
+/// Frames that are shown while customer is waiting at the door.
+#[derive(Component)]
+struct IdleAnimation {
+    /// Index of the character in `city_tiles.png`.
+    character: usize,
+    frame: usize,
+    timer: Timer,
+}
+
+impl IdleAnimation {
+    /// Calm customers look around, while impatient ones stamp their feet.
+    fn index(&self, mood: Mood) -> usize {
+        const LOOK_AROUND: [Direction; 6] = [
+            Direction::Down,
+            Direction::Down,
+            Direction::Left,
+            Direction::Down,
+            Direction::Down,
+            Direction::Right,
+        ];
+        let animation = match mood {
+            Mood::Happy | Mood::Neutral => {
+                MovementAnimation::Standing(LOOK_AROUND[self.frame % LOOK_AROUND.len()])
+            }
+            Mood::Annoyed | Mood::Furious => MovementAnimation::Walking(Direction::Down),
+        };
+        animation.index(self.frame) + self.character * CHARACTER_STRIDE
+    }
+}
+
+/// Text that floats above the customer for a moment.
+#[derive(Component, Deref, DerefMut)]
+struct Reaction(Timer);
+
+fn spawn_customers(
+    points: Query<&DeliveryPoint, Added<DeliveryPoint>>,
+    maps: Query<&MapData<DecorationTile>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut atlas: Local<Option<Handle<TextureAtlas>>>,
+    mut rng: ResMut<GameRng>,
+) {
+    let Ok(map) = maps.get_single() else {
+        return;
+    };
+    let atlas = atlas.get_or_insert_with(|| {
+        texture_atlases.add(TextureAtlas::from_grid(
+            asset_server.load("city_tiles.png"),
+            Vec2::new(16., 16.),
+            27,
+            18,
+            None,
+            None,
+        ))
+    });
+
+    for &point in points.iter() {
+        if point.kind == DeliveryPointKind::Brewery {
+            continue;
+        }
+        let animation = IdleAnimation {
+            character: rng.gen_range(1..CHARACTER_COUNT),
+            frame: 0,
+            timer: Timer::from_seconds(Mood::Happy.frame_time(), TimerMode::Repeating),
+        };
+        let position = tile_to_world(map.size(), point.x, point.y);
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: atlas.clone(),
+                sprite: TextureAtlasSprite::new(animation.index(Mood::Happy)),
+                transform: Transform::from_translation(position.extend(depth::SPRITES)),
+                ..default()
+            },
+            Customer::new(point),
+            Speaker::new(match point.kind {
+                DeliveryPointKind::Bar => "bartender",
+                _ => "customer",
+            })
+            .at(point),
+            Interactable {
+                prompt: Some(String::from("Talk")),
+                ..Interactable::new(TILE_SIZE)
+            },
+            animation,
+            YSort,
+            OnGameScreen,
+        ));
+    }
+}
+
+/// Drains patience of the customers that wait for the active orders.
+fn wait(time: Res<Time>, orders: Res<ActiveOrders>, mut customers: Query<&mut Customer>) {
+    for mut customer in &mut customers {
+        let waiting = orders.iter().any(|order| order.dropoff == customer.point);
+        let patience = if waiting {
+            customer.patience - time.delta_seconds() / PATIENCE_TIME
+        } else {
+            customer.patience + time.delta_seconds() / RECOVERY_TIME
+        };
+        customer.patience = patience.clamp(0., 1.);
+    }
+}
+
+pub fn react(
+    mut events: EventReader<OrderEvent>,
+    mut customers: Query<(Entity, &mut Customer)>,
+    standing: Res<Standing>,
+    mut transactions: EventWriter<Transaction>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.iter() {
+        let (dropoff, delivered) = match *event {
+            OrderEvent::Delivered {
+                payout, dropoff, ..
+            } => (dropoff, Some(payout)),
+            OrderEvent::Cancelled { dropoff } => (dropoff, None),
+            OrderEvent::Accepted | OrderEvent::PickedUp => continue,
+        };
+        let Some((entity, mut customer)) = customers
+            .iter_mut()
+            .find(|(_, customer)| customer.point == dropoff)
+        else {
+            continue;
+        };
+
+        let mood = customer.mood();
+        let (message, color) = match delivered {
+            Some(payout) => {
+                let tip = payout
+                    * mood.tip_share()
+                    * reputation::tip_multiplier(standing.customer(&dropoff));
+                if tip > Money::default() {
+                    transactions.send(Transaction::income(TransactionKind::Tip, tip));
+                    (format!("Thanks! +{tip}"), Color::rgb(0.4, 1.0, 0.4))
+                } else if mood == Mood::Furious {
+                    (String::from("Finally..."), Color::rgb(1.0, 0.6, 0.3))
+                } else {
+                    (String::from("Thanks"), Color::WHITE)
+                }
+            }
+            None => (String::from("Forget it!"), Color::rgb(1.0, 0.3, 0.3)),
+        };
+        customer.patience = 1.;
+        say(&mut commands, entity, message, color, &asset_server);
+    }
+}
+
+/// Shows text that floats above the customer for a moment.
+fn say(
+    commands: &mut Commands,
+    customer: Entity,
+    message: impl Into<String>,
+    color: Color,
+    asset_server: &AssetServer,
+) {
+    let style = TextStyle {
+        font: asset_server.load(FONT),
+        font_size: 32.,
+        color,
+    };
+    commands.entity(customer).with_children(|parent| {
+        parent.spawn((
+            Text2dBundle {
+                text: Text::from_section(message, style),
+                // Text is rendered at high resolution and scaled down to stay sharp.
+                transform: Transform::from_xyz(0., 12., depth::OVERHANG)
+                    .with_scale(Vec3::splat(0.25)),
+                ..default()
+            },
+            Reaction(Timer::from_seconds(REACTION_TIME, TimerMode::Once)),
+        ));
+    });
+}
+
+fn animate_customers(
+    time: Res<Time>,
+    mut customers: Query<(&Customer, &mut IdleAnimation, &mut TextureAtlasSprite)>,
+) {
+    for (customer, mut animation, mut sprite) in &mut customers {
+        let mood = customer.mood();
+        let frame_time = std::time::Duration::from_secs_f32(mood.frame_time());
+        if animation.timer.duration() != frame_time {
+            animation.timer.set_duration(frame_time);
+        }
+        if animation.timer.tick(time.delta()).just_finished() {
+            animation.frame += 1;
+        }
+        let index = animation.index(mood);
+        if sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
+
+fn float_reactions(
+    time: Res<Time>,
+    mut reactions: Query<(Entity, &mut Reaction, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, mut reaction, mut transform) in &mut reactions {
+        transform.translation.y += REACTION_SPEED * time.delta_seconds();
+        if reaction.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Depth at which everything in the game world is drawn.
+//!
+//! Layers are drawn in the following order: ground, bases of the buildings, sprites and
+//! overhangs of the buildings. Sprites are additionally sorted by their `y`, so the ones closer
+//! to the bottom of the screen are drawn in front of the others.
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use super::GameSystemSet;
+
+pub const GROUND: f32 = 0.;
+pub const BUILDING_BASE: f32 = 1.;
+/// Sprites are placed in range from `SPRITES` to `SPRITES + 1`.
+pub const SPRITES: f32 = 2.;
+pub const OVERHANG: f32 = 10.;
+
+/// Distance from the origin in which sorting of sprites is precise.
+const Y_SORT_RANGE: f32 = 100_000.;
+
+pub struct DepthPlugin;
+
+impl Plugin for DepthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            y_sort
+                .in_base_set(CoreSet::PostUpdate)
+                .before(TransformSystem::TransformPropagate)
+                .in_set(GameSystemSet),
+        );
+    }
+}
+
+/// Sprite that is drawn in front of the sprites located above it.
+#[derive(Component)]
+pub struct YSort;
+
+fn y_sort(mut query: Query<&mut Transform, With<YSort>>) {
+    for mut transform in &mut query {
+        let y = transform.translation.y.clamp(-Y_SORT_RANGE, Y_SORT_RANGE);
+        transform.translation.z = SPRITES + 0.5 - y / (2. * Y_SORT_RANGE);
+    }
+}
@@ -0,0 +1,440 @@
+//! Conversations with customers, bartenders and the brewery boss.
+//!
+//! Dialogues are loaded from [DIALOGUE_FILE] when the game starts. Interacting with a
+//! [Speaker] opens the dialogue box, where text is typed out letter by letter and choices are
+//! selected by any of the couriers. Couriers stand still during the conversation, which
+//! is about the order of the team of the courier that started it.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::{
+    dialogue::{DialogueSet, DialogueState, Effect, Node},
+    map::{
+        delivery::{DeliveryPoint, DeliveryPointKind},
+        tile::DecorationTile,
+        MapData,
+    },
+    menu::FONT,
+    replay::Actions,
+    GameState,
+};
+
+use super::{
+    clock::GameClock,
+    customer::Customer,
+    depth::{self, YSort},
+    economy::{Economy, Money, Transaction, TransactionKind},
+    input::StepInput,
+    interaction::{self, Interactable, Interacted},
+    map::{tile_to_world, TILE_SIZE},
+    order::{ActiveOrders, Order, OrderRequest},
+    player::{Direction, MovementAnimation, Player, CHARACTER_STRIDE},
+    reputation::Standing,
+    simulation::SimulationSystems,
+    GameSystemSet, OnGameScreen,
+};
+
+const DIALOGUE_FILE: &str = "assets/dialogues.ron";
+/// Letters of the dialogue text that are typed out every second.
+const TYPING_SPEED: f32 = 40.;
+/// Character of `city_tiles.png` that plays the brewery boss.
+const BOSS_CHARACTER: usize = 5;
+const FONT_SIZE: f32 = 24.;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const SPEAKER_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const SELECTED_COLOR: Color = Color::rgb(0.3, 1.0, 0.4);
+const BOX_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Conversation>()
+            .add_startup_system(load_dialogues)
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_step_systems((spawn_boss, advance, start, type_text).after(interaction::interact))
+            .add_system(update_box.in_set(GameSystemSet));
+    }
+}
+
+/// Character that the courier is able to talk to.
+#[derive(Component, Debug, Clone)]
+pub struct Speaker {
+    /// Name of the dialogue in [DIALOGUE_FILE].
+    pub dialogue: String,
+    /// Delivery point that the character belongs to.
+    pub place: Option<DeliveryPoint>,
+}
+
+impl Speaker {
+    pub fn new(dialogue: &str) -> Self {
+        Self {
+            dialogue: String::from(dialogue),
+            place: None,
+        }
+    }
+
+    pub fn at(mut self, place: DeliveryPoint) -> Self {
+        self.place = Some(place);
+        self
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+struct Dialogues(DialogueSet);
+
+/// Dialogue that the courier takes part in right now, if any.
+#[derive(Resource, Debug, Default)]
+pub struct Conversation(Option<Talk>);
+
+#[derive(Debug)]
+struct Talk {
+    speaker: Entity,
+    /// Team of the courier that talks to the speaker.
+    team: usize,
+    dialogue: String,
+    node: String,
+    /// Amount of letters of the text that are already typed out.
+    typed: f32,
+    /// Index of the selected choice among the available ones.
+    selected: usize,
+}
+
+/// Run condition that holds while courier talks to someone.
+///
+/// Nobody talks when the dialogues aren't a part of the game, like in the headless simulation.
+pub fn in_dialogue(conversation: Option<Res<Conversation>>) -> bool {
+    conversation.is_some_and(|conversation| conversation.0.is_some())
+}
+
+#[derive(Component)]
+struct DialogueBox;
+
+#[derive(Component)]
+struct SpeakerText;
+
+#[derive(Component)]
+struct LineText;
+
+#[derive(Component)]
+struct ChoicesText;
+
+fn load_dialogues(mut commands: Commands) {
+    let dialogues = DialogueSet::load(Path::new(DIALOGUE_FILE)).unwrap_or_else(|err| {
+        warn!("{err}");
+        DialogueSet::default()
+    });
+    commands.insert_resource(Dialogues(dialogues));
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Conversation::default());
+
+    let style = TextStyle {
+        font: asset_server.load(FONT),
+        font_size: FONT_SIZE,
+        color: TEXT_COLOR,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Percent(15.),
+                        right: Val::Percent(15.),
+                        bottom: Val::Px(10.),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    gap: Size::height(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: BOX_COLOR.into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            DialogueBox,
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        color: SPEAKER_COLOR,
+                        ..style.clone()
+                    },
+                ),
+                SpeakerText,
+            ));
+            parent.spawn((TextBundle::from_section("", style.clone()), LineText));
+            parent.spawn((TextBundle::from_section("", style), ChoicesText));
+        });
+}
+
+/// Places the boss at the door of the brewery.
+fn spawn_boss(
+    points: Query<&DeliveryPoint, Added<DeliveryPoint>>,
+    maps: Query<&MapData<DecorationTile>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let Ok(map) = maps.get_single() else {
+        return;
+    };
+    for &point in points.iter() {
+        if point.kind != DeliveryPointKind::Brewery {
+            continue;
+        }
+        let atlas = texture_atlases.add(TextureAtlas::from_grid(
+            asset_server.load("city_tiles.png"),
+            Vec2::new(16., 16.),
+            27,
+            18,
+            None,
+            None,
+        ));
+        let index = MovementAnimation::Standing(Direction::Down).index(0)
+            + BOSS_CHARACTER * CHARACTER_STRIDE;
+        let position = tile_to_world(map.size(), point.x, point.y);
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: atlas,
+                sprite: TextureAtlasSprite::new(index),
+                transform: Transform::from_translation(position.extend(depth::SPRITES)),
+                ..default()
+            },
+            Speaker::new("boss").at(point),
+            Interactable {
+                prompt: Some(String::from("Talk to the boss")),
+                ..Interactable::new(TILE_SIZE)
+            },
+            YSort,
+            OnGameScreen,
+        ));
+    }
+}
+
+/// Returns node of the conversation that is shown right now.
+fn current_node<'a>(dialogues: &'a Dialogues, talk: &Talk) -> Option<&'a Node> {
+    dialogues
+        .0
+        .dialogues
+        .get(&talk.dialogue)?
+        .nodes
+        .get(&talk.node)
+}
+
+/// Describes the game from the point of view of the speaker.
+fn dialogue_state(
+    speaker: Option<&Speaker>,
+    economy: &Economy,
+    order: Option<&Order>,
+    clock: &GameClock,
+    standing: &Standing,
+) -> DialogueState {
+    let place = speaker.and_then(|speaker| speaker.place);
+    let reputation = match place {
+        Some(place) if place.kind != DeliveryPointKind::Brewery => standing.customer(&place),
+        _ => standing.reputation.score,
+    };
+    DialogueState {
+        cash: economy.cash().as_cents(),
+        has_order: order.is_some(),
+        picked_up: order.is_some_and(|order| order.picked_up()),
+        open: place.is_some_and(|place| clock.is_open(place.kind)),
+        waiting: place.is_some_and(|place| order.is_some_and(|order| order.dropoff == place)),
+        reputation,
+    }
+}
+
+/// Moves the selection between choices and confirms it.
+#[allow(clippy::too_many_arguments)]
+fn advance(
+    input: Res<StepInput>,
+    dialogues: Res<Dialogues>,
+    mut conversation: ResMut<Conversation>,
+    (economy, orders, clock, standing): (
+        Res<Economy>,
+        Res<ActiveOrders>,
+        Res<GameClock>,
+        Res<Standing>,
+    ),
+    speakers: Query<&Speaker>,
+    mut customers: Query<&mut Customer>,
+    mut requests: EventWriter<OrderRequest>,
+    mut transactions: EventWriter<Transaction>,
+) {
+    let Some(talk) = &mut conversation.0 else {
+        return;
+    };
+    let Some(node) = current_node(&dialogues, talk) else {
+        conversation.0 = None;
+        return;
+    };
+    let pressed = input.any_just_pressed();
+
+    let speaker = speakers.get(talk.speaker).ok();
+    let state = dialogue_state(speaker, &economy, orders.get(talk.team), &clock, &standing);
+    let choices: Vec<_> = node.available_choices(&state).collect();
+    if pressed.contains(Actions::UP) {
+        talk.selected = talk.selected.saturating_sub(1);
+    }
+    if pressed.contains(Actions::DOWN) {
+        talk.selected = (talk.selected + 1).min(choices.len().saturating_sub(1));
+    }
+    if !pressed.intersects(Actions::INTERACT | Actions::CONFIRM) {
+        return;
+    }
+
+    // Text that is still being typed is shown whole first.
+    let length = node.text.chars().count() as f32;
+    if talk.typed < length {
+        talk.typed = length;
+        return;
+    }
+    let Some(choice) = choices.get(talk.selected) else {
+        conversation.0 = None;
+        return;
+    };
+    for effect in &choice.effects {
+        match *effect {
+            Effect::AcceptOrder => requests.send(OrderRequest::New(talk.team)),
+            Effect::CancelOrder => requests.send(OrderRequest::Cancel(talk.team)),
+            Effect::Calm(amount) => {
+                if let Ok(mut customer) = customers.get_mut(talk.speaker) {
+                    customer.calm(amount);
+                }
+            }
+            Effect::Cash(cents) if cents >= 0 => transactions.send(Transaction::income(
+                TransactionKind::Deal,
+                Money::cents(cents),
+            )),
+            Effect::Cash(cents) => transactions.send(Transaction::expense(
+                TransactionKind::Deal,
+                Money::cents(-cents),
+            )),
+        }
+    }
+    match &choice.next {
+        Some(next) => {
+            talk.node = next.clone();
+            talk.typed = 0.;
+            talk.selected = 0;
+        }
+        None => conversation.0 = None,
+    }
+}
+
+/// Opens the dialogue of the speaker that courier interacts with.
+fn start(
+    mut interactions: EventReader<Interacted>,
+    speakers: Query<&Speaker>,
+    players: Query<&Player>,
+    dialogues: Res<Dialogues>,
+    mut conversation: ResMut<Conversation>,
+) {
+    for &Interacted { target, courier } in interactions.iter() {
+        let (Ok(speaker), Ok(player)) = (speakers.get(target), players.get(courier)) else {
+            continue;
+        };
+        let Some(dialogue) = dialogues.0.dialogues.get(&speaker.dialogue) else {
+            continue;
+        };
+        conversation.0 = Some(Talk {
+            speaker: target,
+            team: player.team,
+            dialogue: speaker.dialogue.clone(),
+            node: dialogue.start.clone(),
+            typed: 0.,
+            selected: 0,
+        });
+    }
+}
+
+fn type_text(time: Res<Time>, mut conversation: ResMut<Conversation>) {
+    if let Some(talk) = &mut conversation.0 {
+        talk.typed += TYPING_SPEED * time.delta_seconds();
+    }
+}
+
+fn update_box(
+    dialogues: Res<Dialogues>,
+    conversation: Res<Conversation>,
+    (economy, orders, clock, standing): (
+        Res<Economy>,
+        Res<ActiveOrders>,
+        Res<GameClock>,
+        Res<Standing>,
+    ),
+    speakers: Query<&Speaker>,
+    mut dialogue_box: Query<&mut Visibility, With<DialogueBox>>,
+    mut texts: ParamSet<(
+        Query<&mut Text, With<SpeakerText>>,
+        Query<&mut Text, With<LineText>>,
+        Query<&mut Text, With<ChoicesText>>,
+    )>,
+) {
+    let talk = conversation.0.as_ref();
+    let node = talk.and_then(|talk| Some((talk, current_node(&dialogues, talk)?)));
+    for mut visibility in &mut dialogue_box {
+        *visibility = if node.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    let Some((talk, node)) = node else {
+        return;
+    };
+
+    for mut text in &mut texts.p0() {
+        text.sections[0].value = node.speaker.clone();
+    }
+    let typed = node
+        .text
+        .chars()
+        .take(talk.typed as usize)
+        .collect::<String>();
+    for mut text in &mut texts.p1() {
+        if text.sections[0].value != typed {
+            text.sections[0].value = typed.clone();
+        }
+    }
+
+    let speaker = speakers.get(talk.speaker).ok();
+    let state = dialogue_state(speaker, &economy, orders.get(talk.team), &clock, &standing);
+    let finished = talk.typed >= node.text.chars().count() as f32;
+    for mut text in &mut texts.p2() {
+        // There is always at least one section, which keeps the style.
+        let style = text.sections[0].style.clone();
+        let mut sections: Vec<_> = node
+            .available_choices(&state)
+            .enumerate()
+            .filter(|_| finished)
+            .map(|(i, choice)| {
+                let (marker, color) = if i == talk.selected {
+                    ("> ", SELECTED_COLOR)
+                } else {
+                    ("  ", TEXT_COLOR)
+                };
+                TextSection::new(
+                    format!("{marker}{}\n", choice.text),
+                    TextStyle {
+                        color,
+                        ..style.clone()
+                    },
+                )
+            })
+            .collect();
+        if sections.is_empty() {
+            sections.push(TextSection::new("", style));
+        }
+        text.sections = sections;
+    }
+}
@@ -0,0 +1,227 @@
+//! Money of the courier and everything it is spent on.
+//!
+//! Gameplay systems send [Transaction] events, which are applied to [Economy] and then
+//! reported back with [CashChanged] events.
+
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub},
+};
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::simulation::{SimulationEvents, SimulationSystems};
+
+/// Cash that the courier has at the start of the game.
+const STARTING_CASH: Money = Money::dollars(50);
+/// Bonus paid for every tile between pickup and dropoff.
+const DISTANCE_BONUS: Money = Money::cents(20);
+/// Part of the payout that is lost for every second of lateness.
+const LATENESS_PENALTY: f32 = 0.02;
+/// Part of the payout that is paid regardless of lateness.
+const MIN_LATE_PAYOUT: f32 = 0.25;
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Economy>()
+            .add_simulation_event::<Transaction>()
+            .add_simulation_event::<CashChanged>()
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_step_systems((apply_transactions, log_transactions));
+    }
+}
+
+/// Amount of money in cents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Money(i64);
+
+impl Money {
+    pub const fn cents(cents: i64) -> Self {
+        Money(cents)
+    }
+
+    pub const fn dollars(dollars: i64) -> Self {
+        Money(dollars * 100)
+    }
+
+    pub fn as_cents(&self) -> i64 {
+        self.0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.is_negative() { "-" } else { "" };
+        let cents = self.0.abs();
+        write!(f, "{sign}${}.{:02}", cents / 100, cents % 100)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+/// Ratio between two amounts of money.
+impl Div for Money {
+    type Output = f32;
+
+    fn div(self, rhs: Money) -> f32 {
+        self.0 as f32 / rhs.0 as f32
+    }
+}
+
+impl Mul<f32> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: f32) -> Money {
+        Money((self.0 as f32 * rhs).round() as i64)
+    }
+}
+
+/// Cash of the courier and history of its changes.
+#[derive(Resource, Debug, Clone)]
+pub struct Economy {
+    cash: Money,
+    /// All transactions since the start of the game, oldest first.
+    ledger: Vec<Transaction>,
+}
+
+impl Economy {
+    pub fn cash(&self) -> Money {
+        self.cash
+    }
+
+    pub fn can_afford(&self, amount: Money) -> bool {
+        self.cash >= amount
+    }
+
+    /// All transactions since the start of the game, oldest first.
+    pub fn ledger(&self) -> &[Transaction] {
+        &self.ledger
+    }
+}
+
+impl Default for Economy {
+    fn default() -> Self {
+        Self {
+            cash: STARTING_CASH,
+            ledger: Vec::new(),
+        }
+    }
+}
+
+/// Change of the cash. Positive amount is income, negative is expense.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transaction {
+    pub kind: TransactionKind,
+    pub amount: Money,
+}
+
+impl Transaction {
+    pub fn income(kind: TransactionKind, amount: Money) -> Self {
+        Self { kind, amount }
+    }
+
+    pub fn expense(kind: TransactionKind, amount: Money) -> Self {
+        Self {
+            kind,
+            amount: -amount,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransactionKind {
+    /// Payout for the delivered order.
+    Delivery,
+    /// Tip from the satisfied customer.
+    Tip,
+    /// Beer bought at the brewery.
+    Stock,
+    /// Penalty for failed order.
+    Fine,
+    /// Fuel burnt by the vehicle.
+    Fuel,
+    /// Repairs of the vehicle after collision.
+    Repair,
+    /// Money that changed hands in a dialogue.
+    Deal,
+}
+
+/// Sent after [Transaction] is applied to [Economy].
+#[derive(Debug, Clone, Copy)]
+pub struct CashChanged {
+    pub cash: Money,
+    pub transaction: Transaction,
+}
+
+/// Calculates payout for the order.
+///
+/// `distance` is measured in tiles and `lateness` in seconds after the deadline.
+pub fn payout(value: Money, distance: f32, lateness: f32) -> Money {
+    let full = value + DISTANCE_BONUS * distance;
+    let share = 1. - lateness.max(0.) * LATENESS_PENALTY;
+    full * share.max(MIN_LATE_PAYOUT)
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(Economy::default());
+}
+
+fn apply_transactions(
+    mut economy: ResMut<Economy>,
+    mut transactions: EventReader<Transaction>,
+    mut cash_changed: EventWriter<CashChanged>,
+) {
+    for &transaction in transactions.iter() {
+        economy.cash += transaction.amount;
+        economy.ledger.push(transaction);
+        cash_changed.send(CashChanged {
+            cash: economy.cash,
+            transaction,
+        });
+    }
+}
+
+fn log_transactions(mut cash_changed: EventReader<CashChanged>) {
+    for CashChanged { cash, transaction } in cash_changed.iter() {
+        info!(
+            "{:?}: {}, cash: {cash}",
+            transaction.kind, transaction.amount
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Overlay that shows state of the couriers during the game.
+
+use bevy::prelude::*;
+
+use crate::{map::delivery::DeliveryPointKind, menu::FONT, GameState};
+
+use super::{
+    cargo::{Cargo, CargoBroken},
+    clock::{DayEnded, GameClock},
+    economy::Economy,
+    interaction::{Focus, Interactable},
+    order::{ActiveOrders, Order, OrderEvent, TeamEarnings},
+    player::{Controls, Player},
+    simulation::{SimulationSystems, SimulationTime},
+    vehicle::VehicleEvent,
+    GameSystemSet, OnGameScreen,
+};
+
+const FONT_SIZE: f32 = 24.0;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+/// Time for which notification stays on the screen.
+const NOTIFICATION_TIME: f32 = 3.0;
+/// Time for which summary of the day stays on the screen.
+const SUMMARY_TIME: f32 = 10.0;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_systems(
+                (
+                    update_cash,
+                    update_cargo,
+                    update_order,
+                    update_clock,
+                    update_prompt,
+                    expire_notifications,
+                )
+                    .in_set(GameSystemSet),
+            )
+            // Notifications are about the events of the simulation, which are only kept for a
+            // step after the one they were sent in.
+            .add_step_system(notify);
+    }
+}
+
+#[derive(Component)]
+struct CashText;
+
+#[derive(Component)]
+struct CargoText;
+
+#[derive(Component)]
+struct OrderText;
+
+#[derive(Component)]
+struct ClockText;
+
+/// Action offered by the focused interactable.
+#[derive(Component)]
+struct PromptText;
+
+/// Container of the notifications.
+#[derive(Component)]
+struct Notifications;
+
+/// Message that disappears once the timer is finished.
+#[derive(Component)]
+struct Notification(Timer);
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load(FONT),
+        font_size: FONT_SIZE,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceBetween,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((TextBundle::from_section("", style.clone()), CashText));
+                            parent.spawn((TextBundle::from_section("", style.clone()), CargoText));
+                            parent.spawn((TextBundle::from_section("", style.clone()), OrderText));
+                        });
+                    parent.spawn((TextBundle::from_section("", style.clone()), ClockText));
+                });
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        ..default()
+                    },
+                    ..default()
+                },
+                Notifications,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(0.0),
+                            right: Val::Px(0.0),
+                            bottom: Val::Percent(25.0),
+                            ..default()
+                        },
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((TextBundle::from_section("", style.clone()), PromptText));
+                });
+        });
+}
+
+fn update_cash(economy: Res<Economy>, mut text: Query<&mut Text, With<CashText>>) {
+    for mut text in &mut text {
+        text.sections[0].value = format!("Cash: {}", economy.cash());
+    }
+}
+
+fn update_cargo(
+    changed: Query<(), (With<Player>, Changed<Cargo>)>,
+    players: Query<(&Player, &Cargo)>,
+    mut text: Query<&mut Text, With<CargoText>>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+    let mut players: Vec<_> = players.iter().collect();
+    players.sort_by_key(|(player, _)| player.index);
+    let count = players.len();
+    let value = players
+        .iter()
+        .map(|(player, cargo)| format!("{}Cargo: {cargo}", label(player.index, count)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    for mut text in &mut text {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn update_order(
+    orders: Res<ActiveOrders>,
+    earnings: Res<TeamEarnings>,
+    time: Res<SimulationTime>,
+    mut text: Query<&mut Text, With<OrderText>>,
+) {
+    let teams = orders.0.len();
+    let value = orders
+        .0
+        .iter()
+        .enumerate()
+        .map(|(team, order)| {
+            let status = describe_order(order.as_ref(), time.0.elapsed_seconds());
+            match earnings.0.get(team) {
+                Some(earned) if teams > 1 => {
+                    format!("{}{status}, earned {earned}", label(team, teams))
+                }
+                _ => status,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    for mut text in &mut text {
+        text.sections[0].value = value.clone();
+    }
+}
+
+/// Prefix of the lines about one of several couriers or teams, like "P2 ".
+fn label(index: usize, count: usize) -> String {
+    if count > 1 {
+        format!("P{} ", index + 1)
+    } else {
+        String::new()
+    }
+}
+
+fn describe_order(order: Option<&Order>, now: f32) -> String {
+    let Some(order) = order else {
+        return String::from("No orders, ask the boss at the brewery");
+    };
+    let task = if order.picked_up() {
+        match order.dropoff.kind {
+            DeliveryPointKind::Bar => "Deliver to the bar",
+            _ => "Deliver to the customer",
+        }
+    } else {
+        "Pick up at the brewery"
+    };
+    let time_left = order.deadline - now;
+    if time_left >= 0. {
+        format!("{task}: {:.0}s", time_left.ceil())
+    } else {
+        format!("{task}: late by {:.0}s", -time_left.floor())
+    }
+}
+
+fn update_clock(clock: Res<GameClock>, mut text: Query<&mut Text, With<ClockText>>) {
+    for mut text in &mut text {
+        text.sections[0].value = clock.to_string();
+    }
+}
+
+fn update_prompt(
+    players: Query<(&Focus, &Controls), With<Player>>,
+    interactables: Query<&Interactable>,
+    mut text: Query<&mut Text, With<PromptText>>,
+) {
+    let value = players
+        .iter()
+        .filter_map(|(focus, controls)| {
+            let interactable = interactables.get(focus.0?).ok()?;
+            let prompt = interactable.prompt.as_ref()?;
+            Some(format!("{:?}: {prompt}", controls.interact))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    for mut text in &mut text {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn notify(
+    mut events: EventReader<OrderEvent>,
+    mut broken: EventReader<CargoBroken>,
+    mut vehicle_events: EventReader<VehicleEvent>,
+    mut day_ended: EventReader<DayEnded>,
+    notifications: Query<Entity, With<Notifications>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let Ok(notifications) = notifications.get_single() else {
+        return;
+    };
+    let order_messages = events.iter().map(|event| match event {
+        OrderEvent::Accepted => String::from("New order!"),
+        OrderEvent::PickedUp => String::from("Beer picked up"),
+        OrderEvent::Delivered { payout, .. } => format!("Delivered! Earned {payout}"),
+        OrderEvent::Cancelled { .. } => String::from("Order cancelled, you were fined"),
+    });
+    let broken_messages = broken.iter().map(|&CargoBroken { item, count, .. }| {
+        format!("Crash! {count} {} broke", item.name(count))
+    });
+    let vehicle_messages = vehicle_events.iter().map(|event| match event {
+        VehicleEvent::Entered(kind) => format!("Took the {}", kind.name()),
+        VehicleEvent::Parked(kind) => format!("Parked the {}", kind.name()),
+        VehicleEvent::CargoDoesNotFit(kind) => {
+            format!("Cargo doesn't fit, {} can't be used", kind.name())
+        }
+    });
+    let summaries = day_ended.iter().map(|DayEnded(summary)| {
+        let message = format!(
+            "Day {} is over\nDeliveries: {}, cancelled: {}\nIncome: {}, expenses: {}\nProfit: {}",
+            summary.day,
+            summary.deliveries,
+            summary.cancellations,
+            summary.income,
+            summary.expenses,
+            summary.profit(),
+        );
+        (message, SUMMARY_TIME)
+    });
+    for (message, duration) in order_messages
+        .chain(broken_messages)
+        .chain(vehicle_messages)
+        .map(|message| (message, NOTIFICATION_TIME))
+        .chain(summaries)
+    {
+        let style = TextStyle {
+            font: asset_server.load(FONT),
+            font_size: FONT_SIZE,
+            color: TEXT_COLOR,
+        };
+        commands.entity(notifications).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(message, style),
+                Notification(Timer::from_seconds(duration, TimerMode::Once)),
+            ));
+        });
+    }
+}
+
+fn expire_notifications(
+    time: Res<Time>,
+    mut notifications: Query<(Entity, &mut Notification)>,
+    mut commands: Commands,
+) {
+    for (entity, mut notification) in &mut notifications {
+        if notification.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Actions of the local players.
+//!
+//! Gameplay systems don't read the keyboard, since they run in simulation steps that don't
+//! line up with the frames. Keyboard and gamepads are sampled every frame instead by the
+//! [DeviceInputPlugin], and actions held since the last step are turned into [StepInput] at
+//! the start of the step. Short presses that start and end between two steps still count.
+//! While a [Playback] runs, actions come from the replay instead. Without the devices, like in
+//! the headless simulation, actions are held with [SampledInput::hold].
+
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{replay::Actions, GameState};
+
+use super::{
+    player::{Controls, MAX_PLAYERS},
+    replay::Playback,
+    simulation::StepSet,
+};
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StepInput>()
+            .init_resource::<SampledInput>()
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_system(
+                read_input
+                    .run_if(not(resource_exists::<Playback>()))
+                    .in_set(StepSet::Input)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+    }
+}
+
+/// Samples the keyboard and gamepads of the local players.
+pub struct DeviceInputPlugin;
+
+impl Plugin for DeviceInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            sample
+                .in_base_set(CoreSet::PreUpdate)
+                .after(InputSystem)
+                .run_if(in_state(GameState::Game)),
+        );
+    }
+}
+
+/// Actions of every local player in the current step, by the player index.
+#[derive(Resource, Debug, Default)]
+pub struct StepInput {
+    current: [Actions; MAX_PLAYERS],
+    previous: [Actions; MAX_PLAYERS],
+}
+
+impl StepInput {
+    /// Sets actions of the next step.
+    pub fn advance(&mut self, actions: [Actions; MAX_PLAYERS]) {
+        self.previous = self.current;
+        self.current = actions;
+    }
+
+    pub fn actions(&self) -> [Actions; MAX_PLAYERS] {
+        self.current
+    }
+
+    /// Returns actions that the player performs in the step.
+    pub fn pressed(&self, player: usize) -> Actions {
+        self.current.get(player).copied().unwrap_or_default()
+    }
+
+    /// Returns actions that the player started to perform in the step.
+    pub fn just_pressed(&self, player: usize) -> Actions {
+        let previous = self.previous.get(player).copied().unwrap_or_default();
+        self.pressed(player).started_since(previous)
+    }
+
+    /// Returns actions that any of the players started to perform in the step.
+    pub fn any_just_pressed(&self) -> Actions {
+        (0..MAX_PLAYERS).fold(Actions::NONE, |actions, player| {
+            actions | self.just_pressed(player)
+        })
+    }
+}
+
+/// Actions gathered from the keyboard and gamepads since the last step.
+#[derive(Resource, Debug, Default)]
+pub struct SampledInput {
+    /// Actions that are performed in the latest frame.
+    held: [Actions; MAX_PLAYERS],
+    /// Actions that were performed in any frame since the last step.
+    pressed: [Actions; MAX_PLAYERS],
+}
+
+impl SampledInput {
+    /// Makes the player perform provided actions in every step until other ones are held.
+    pub fn hold(&mut self, player: usize, actions: Actions) {
+        self.held[player] = actions;
+        self.pressed[player] |= actions;
+    }
+}
+
+fn setup(mut input: ResMut<StepInput>, mut sampled: ResMut<SampledInput>) {
+    *input = StepInput::default();
+    *sampled = SampledInput::default();
+}
+
+fn sample(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut sampled: ResMut<SampledInput>,
+) {
+    let mut held = [Actions::NONE; MAX_PLAYERS];
+    for (player, actions) in held.iter_mut().enumerate() {
+        *actions = Controls::for_player(player).actions(&keyboard_input);
+    }
+    // Gamepad and the confirmation keys belong to the first player, mostly for the dialogues,
+    // which the south button of the gamepad starts as well.
+    if keyboard_input.any_pressed([KeyCode::Return, KeyCode::Space]) {
+        held[0] |= Actions::CONFIRM;
+    }
+    for gamepad in gamepads.iter() {
+        let pressed = |button| gamepad_input.pressed(GamepadButton::new(gamepad, button));
+        if pressed(GamepadButtonType::DPadUp) {
+            held[0] |= Actions::UP;
+        }
+        if pressed(GamepadButtonType::DPadDown) {
+            held[0] |= Actions::DOWN;
+        }
+        if pressed(GamepadButtonType::South) {
+            held[0] |= Actions::CONFIRM | Actions::INTERACT;
+        }
+    }
+
+    for (player, actions) in held.into_iter().enumerate() {
+        sampled.hold(player, actions);
+    }
+}
+
+pub fn read_input(mut input: ResMut<StepInput>, mut sampled: ResMut<SampledInput>) {
+    input.advance(sampled.pressed);
+    sampled.pressed = sampled.held;
+}
@@ -0,0 +1,110 @@
+//! Objects that courier is able to interact with.
+//!
+//! The nearest [Interactable] in reach of every courier that has a prompt is focused, and its
+//! prompt is shown by the HUD. Interaction action of the courier sends [Interacted] with the
+//! focused entity. Modules that own the objects keep their prompts up to date and handle the
+//! events.
+
+use bevy::prelude::*;
+
+use crate::replay::Actions;
+
+use super::{
+    dialogue::in_dialogue,
+    input::StepInput,
+    player::Player,
+    simulation::{SimulationEvents, SimulationSystems},
+};
+
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_event::<Interacted>()
+            .add_step_systems((focus_nearest, interact.run_if(not(in_dialogue))));
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Interactable {
+    /// Distance from the courier at which interaction is possible.
+    pub radius: f32,
+    /// Action that is offered to the courier, like "Pick up crates". Nothing is offered
+    /// while it's `None`.
+    pub prompt: Option<String>,
+    /// Interactables with higher priority are preferred over the closer ones.
+    pub priority: i32,
+    /// Courier that the action is offered to, or `None` if it's offered to everyone.
+    pub courier: Option<Entity>,
+}
+
+impl Interactable {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            prompt: None,
+            priority: 0,
+            courier: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Interactable that the courier interacts with when its interaction key is pressed.
+#[derive(Component, Debug, Default)]
+pub struct Focus(pub Option<Entity>);
+
+/// Sent when courier interacts with the entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interacted {
+    pub target: Entity,
+    pub courier: Entity,
+}
+
+fn focus_nearest(
+    mut players: Query<(Entity, &Transform, &mut Focus), With<Player>>,
+    interactables: Query<(Entity, &Transform, &Interactable), Without<Player>>,
+) {
+    for (courier, player, mut focus) in &mut players {
+        let position = player.translation.truncate();
+        let nearest = interactables
+            .iter()
+            .filter(|(_, _, interactable)| interactable.prompt.is_some())
+            .filter(|(_, _, interactable)| {
+                interactable.courier.is_none() || interactable.courier == Some(courier)
+            })
+            .map(|(entity, transform, interactable)| {
+                let distance = transform.translation.truncate().distance(position);
+                (entity, interactable, distance)
+            })
+            .filter(|(_, interactable, distance)| *distance <= interactable.radius)
+            .max_by(|(_, a, a_distance), (_, b, b_distance)| {
+                a.priority
+                    .cmp(&b.priority)
+                    .then(b_distance.total_cmp(a_distance))
+            })
+            .map(|(entity, _, _)| entity);
+        if focus.0 != nearest {
+            focus.0 = nearest;
+        }
+    }
+}
+
+pub fn interact(
+    input: Res<StepInput>,
+    players: Query<(Entity, &Player, &Focus)>,
+    mut events: EventWriter<Interacted>,
+) {
+    for (courier, player, focus) in &players {
+        if !input.just_pressed(player.index).contains(Actions::INTERACT) {
+            continue;
+        }
+        if let Some(target) = focus.0 {
+            events.send(Interacted { target, courier });
+        }
+    }
+}
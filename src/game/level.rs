@@ -0,0 +1,339 @@
+//! Levels of the campaign.
+//!
+//! Campaign is loaded from [CAMPAIGN_FILE] and progress from [SAVE_FILE] when the game starts.
+//! Level that is selected in the menu is kept in [CurrentLevel], and its objectives are
+//! tracked until they are all met or the closing hour comes. Outside of the campaign the shift
+//! lasts until the midnight, or until the time runs out in the arcade mode. Stars and score of
+//! the finished shift are saved right away and [ShiftEnded] is sent, and courier returns to the
+//! menu with [RETURN_KEY] afterwards.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::{
+    campaign::{Campaign, Level, LevelStats, Objective},
+    menu::FONT,
+    save::SaveGame,
+    score::Scoring,
+    GameState,
+};
+
+use super::{
+    arcade::ArcadeRun,
+    cargo::CargoBroken,
+    clock::GameClock,
+    economy::{CashChanged, Money, TransactionKind},
+    map::MapSelection,
+    order::OrderEvent,
+    player::Coop,
+    replay::Playback,
+    simulation::{SimulationEvents, SimulationSystems},
+    vehicle::{Garage, VehicleKind},
+    GameSystemSet, OnGameScreen,
+};
+
+const CAMPAIGN_FILE: &str = "assets/campaign.ron";
+pub const SAVE_FILE: &str = "save.ron";
+pub const RETURN_KEY: KeyCode = KeyCode::Return;
+const FONT_SIZE: f32 = 24.;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const DONE_COLOR: Color = Color::rgb(0.3, 1.0, 0.4);
+const RESULT_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLevel>()
+            .init_resource::<LevelRun>()
+            .add_simulation_event::<ShiftEnded>()
+            .add_startup_system(load_campaign)
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_step_systems((record_stats, finish))
+            .add_systems((update_panel, return_to_menu).chain().in_set(GameSystemSet));
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Levels(pub Campaign);
+
+/// Progress that is written to [SAVE_FILE].
+#[derive(Resource, Debug, Default)]
+pub struct Save(pub SaveGame);
+
+impl Save {
+    pub fn write(&self) {
+        if let Err(err) = self.0.save(Path::new(SAVE_FILE)) {
+            error!("{err}");
+        }
+    }
+}
+
+/// Level that is played, or `None` outside of the campaign.
+#[derive(Resource, Debug, Default)]
+pub struct CurrentLevel(pub Option<Level>);
+
+/// Results of the current level.
+#[derive(Resource, Debug, Default)]
+struct LevelRun {
+    stats: LevelStats,
+    /// Stars earned once the shift is over, which is zero for failed level or outside of the
+    /// campaign.
+    result: Option<u32>,
+}
+
+/// Sent once the shift is over and its score is saved.
+#[derive(Debug, Clone)]
+pub struct ShiftEnded {
+    /// Key of the leaderboard table that the score was recorded in.
+    pub table: String,
+    /// Name of the level or map that was played.
+    pub name: String,
+    pub scoring: Scoring,
+    pub stats: LevelStats,
+    /// Position in the table, if the score got into it.
+    pub rank: Option<usize>,
+}
+
+/// List of objectives and their progress.
+#[derive(Component)]
+struct ObjectivesText;
+
+fn load_campaign(mut commands: Commands) {
+    let campaign = Campaign::load(Path::new(CAMPAIGN_FILE)).unwrap_or_else(|err| {
+        error!("{err}");
+        Campaign::default()
+    });
+    let save = SaveGame::load(Path::new(SAVE_FILE)).unwrap_or_else(|err| {
+        error!("{err}");
+        SaveGame::default()
+    });
+    commands.insert_resource(Levels(campaign));
+    commands.insert_resource(save.volume);
+    commands.insert_resource(Save(save));
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level: Res<CurrentLevel>,
+    levels: Res<Levels>,
+    save: Res<Save>,
+    mut garage: ResMut<Garage>,
+) {
+    commands.insert_resource(LevelRun::default());
+    let Some(level) = &level.0 else {
+        *garage = Garage::default();
+        return;
+    };
+    let vehicles = levels.0.unlocked_vehicles(&save.0.campaign);
+    *garage = Garage(vehicles.into_iter().map(VehicleKind::from).collect());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(0.),
+                        right: Val::Px(0.),
+                        top: Val::Px(10.),
+                        ..default()
+                    },
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(8.)),
+                        ..default()
+                    },
+                    background_color: PANEL_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let style = TextStyle {
+                        font: asset_server.load(FONT),
+                        font_size: FONT_SIZE,
+                        color: TEXT_COLOR,
+                    };
+                    // Title, then every objective on its own line.
+                    let sections = std::iter::repeat_n(
+                        TextSection::new("", style),
+                        1 + level.objectives.len(),
+                    );
+                    parent.spawn((TextBundle::from_sections(sections), ObjectivesText));
+                });
+        });
+}
+
+fn record_stats(
+    mut orders: EventReader<OrderEvent>,
+    mut broken: EventReader<CargoBroken>,
+    mut cash_changed: EventReader<CashChanged>,
+    mut run: ResMut<LevelRun>,
+) {
+    if run.result.is_some() {
+        return;
+    }
+    for event in orders.iter() {
+        match event {
+            OrderEvent::Delivered { late, .. } => {
+                run.stats.deliveries += 1;
+                run.stats.late += u32::from(*late);
+            }
+            OrderEvent::Cancelled { .. } => run.stats.cancellations += 1,
+            OrderEvent::Accepted | OrderEvent::PickedUp => {}
+        }
+    }
+    for CargoBroken { count, .. } in broken.iter() {
+        run.stats.broken += count;
+    }
+    // Loans from the boss and fines don't count as earned.
+    for CashChanged { transaction, .. } in cash_changed.iter() {
+        if matches!(
+            transaction.kind,
+            TransactionKind::Delivery | TransactionKind::Tip
+        ) {
+            run.stats.earnings += transaction.amount.as_cents();
+        }
+    }
+}
+
+/// Ends the shift once the objectives are met or the closing hour comes.
+#[allow(clippy::too_many_arguments)]
+fn finish(
+    level: Res<CurrentLevel>,
+    selection: Res<MapSelection>,
+    coop: Res<Coop>,
+    clock: Res<GameClock>,
+    arcade: Option<Res<ArcadeRun>>,
+    mut run: ResMut<LevelRun>,
+    mut save: ResMut<Save>,
+    playback: Option<Res<Playback>>,
+    mut shift_ended: EventWriter<ShiftEnded>,
+) {
+    if run.result.is_some() {
+        return;
+    }
+    let (result, table, scoring) = match (&level.0, arcade) {
+        (Some(level), _) if level.is_complete(&run.stats) => {
+            let stars = level.stars(&run.stats);
+            save.0.campaign.record(&level.id, stars);
+            (stars, level.id.clone(), Scoring::Shift)
+        }
+        (Some(level), _) if clock.day() > 1 || clock.hour() >= level.closing_hour as f32 => {
+            (0, level.id.clone(), Scoring::Shift)
+        }
+        (None, Some(arcade)) if arcade.is_over() => (
+            0,
+            format!("Arcade: {}", selection.shared_name()),
+            Scoring::Earnings,
+        ),
+        // Stats of the co-op add up both couriers, so they aren't compared with solo shifts.
+        (None, None) if clock.day() > 1 && coop.0.is_some() => (
+            0,
+            format!("Co-op: {}", selection.shared_name()),
+            Scoring::Shift,
+        ),
+        (None, None) if clock.day() > 1 => (0, selection.shared_name(), Scoring::Shift),
+        _ => return,
+    };
+    let name = match &level.0 {
+        Some(level) => level.name.clone(),
+        None => table.clone(),
+    };
+    let rank = save.0.leaderboard.record(&table, scoring, run.stats);
+    // Replays don't change the progress.
+    if playback.is_none() {
+        save.write();
+    }
+    run.result = Some(result);
+    shift_ended.send(ShiftEnded {
+        table,
+        name,
+        scoring,
+        stats: run.stats,
+        rank,
+    });
+}
+
+fn describe(objective: &Objective, stats: &LevelStats) -> String {
+    match *objective {
+        Objective::Deliveries(count) => {
+            format!("Deliver {count} orders ({}/{count})", stats.deliveries)
+        }
+        Objective::Earnings(cents) => format!(
+            "Earn {} ({})",
+            Money::cents(cents),
+            Money::cents(stats.earnings)
+        ),
+        Objective::MaxCancellations(count) => {
+            format!("Cancel at most {count} orders ({})", stats.cancellations)
+        }
+        Objective::MaxBroken(count) => {
+            format!("Break at most {count} items ({})", stats.broken)
+        }
+    }
+}
+
+/// Shows stars as filled and empty slots, like "**-".
+pub fn describe_stars(stars: u32, max: u32) -> String {
+    (0..max)
+        .map(|star| if star < stars { '*' } else { '-' })
+        .collect()
+}
+
+fn update_panel(
+    level: Res<CurrentLevel>,
+    run: Res<LevelRun>,
+    mut text: Query<&mut Text, With<ObjectivesText>>,
+) {
+    let (Some(level), Ok(mut text)) = (&level.0, text.get_single_mut()) else {
+        return;
+    };
+    if !run.is_changed() && !text.is_added() {
+        return;
+    }
+    let title = match run.result {
+        None => format!("{}, until {:02}:00", level.name, level.closing_hour),
+        Some(0) => format!("Closing time! {} failed", level.name),
+        Some(stars) => format!(
+            "{} complete! {}",
+            level.name,
+            describe_stars(stars, level.max_stars())
+        ),
+    };
+    text.sections[0].value = title;
+    text.sections[0].style.color = if run.result.is_some() {
+        RESULT_COLOR
+    } else {
+        TEXT_COLOR
+    };
+    for (section, objective) in text.sections[1..].iter_mut().zip(&level.objectives) {
+        section.value = format!("\n{}", describe(objective, &run.stats));
+        section.style.color = if objective.holds(&run.stats) {
+            DONE_COLOR
+        } else {
+            TEXT_COLOR
+        };
+    }
+}
+
+fn return_to_menu(
+    keyboard_input: Res<Input<KeyCode>>,
+    run: Res<LevelRun>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if run.result.is_some() && keyboard_input.just_pressed(RETURN_KEY) {
+        game_state.set(GameState::MainMenu);
+    }
+}
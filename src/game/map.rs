@@ -4,97 +4,349 @@ use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 
 use crate::{
+    campaign::LevelMap,
     map::{
+        generator::CityGenerator,
+        preset,
         tile::{DecorationTile, SolidTile, TileClass},
-        MapData,
+        validation::ValidationRules,
+        MapData, MapLoadError,
     },
     GameState,
 };
 
-use super::OnGameScreen;
+use super::{depth, OnGameScreen};
 
-/// Plugin that stores tilemap logic and data.
+/// Plugin that loads the map of the game.
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system(
+        app.init_resource::<MapSelection>().add_system(
             setup
                 .in_base_set(CoreSet::PreUpdate)
                 .in_schedule(OnEnter(GameState::Game)),
-        )
-        .add_system(
-            spawn_tilemap::<DecorationTile, 0>
+        );
+    }
+}
+
+/// Plugin that draws the layers of the loaded map.
+pub struct TilemapRenderPlugin;
+
+impl Plugin for TilemapRenderPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(
+            spawn_tilemap::<DecorationTile>
                 .in_base_set(CoreSet::Update)
                 .run_if(in_state(GameState::Game)),
         )
         .add_system(
-            spawn_tilemap::<SolidTile, 12>
+            spawn_tilemap::<SolidTile>
                 .in_base_set(CoreSet::Update)
                 .run_if(in_state(GameState::Game)),
         );
     }
 }
 
-fn setup(mut commands: Commands) {
-    let map_data1 = MapData::<DecorationTile>::load(Path::new("decoration.beer_map"));
-    let map_data2 = MapData::<SolidTile>::load(Path::new("solid.beer_map"));
+/// Size of the tile in world units.
+pub const TILE_SIZE: f32 = 16.;
+
+/// Returns world position of the tile center.
+pub fn tile_to_world(map_size: TilemapSize, x: usize, y: usize) -> Vec2 {
+    // Tilemaps are centered around the origin.
+    let offset = Vec2::new(map_size.x as f32 - 1., map_size.y as f32 - 1.) * TILE_SIZE / 2.;
+    Vec2::new(x as f32, y as f32) * TILE_SIZE - offset
+}
+
+/// Returns tile that contains provided world position, if any.
+pub fn world_to_tile(map_size: TilemapSize, position: Vec2) -> Option<(usize, usize)> {
+    let offset = Vec2::new(map_size.x as f32 - 1., map_size.y as f32 - 1.) * TILE_SIZE / 2.;
+    let tile = ((position + offset) / TILE_SIZE).round();
+    let inside =
+        tile.x >= 0. && tile.y >= 0. && tile.x < map_size.x as f32 && tile.y < map_size.y as f32;
+    inside.then_some((tile.x as usize, tile.y as usize))
+}
+
+/// Checks whether courier is unable to walk through provided world position.
+///
+/// Overhanging tiles are drawn above the courier and don't block movement.
+pub fn is_solid(map: &MapData<SolidTile>, position: Vec2) -> bool {
+    world_to_tile(map.size(), position)
+        .and_then(|(x, y)| map.get(x, y))
+        .is_some_and(|tile| !tile.overhang)
+}
+
+/// Checks whether provided world position is located on the road.
+pub fn is_road(map: &MapData<DecorationTile>, position: Vec2) -> bool {
+    world_to_tile(map.size(), position)
+        .and_then(|(x, y)| map.get(x, y))
+        .is_some_and(TileClass::road)
+}
+
+/// Map that is loaded when the game starts.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub enum MapSelection {
+    /// Preset map stored on the disk.
+    #[default]
+    Preset,
+    /// Procedurally generated city with provided seed.
+    Random(u64),
+    /// Procedurally generated city of provided size in blocks.
+    City {
+        seed: u64,
+        blocks_x: usize,
+        blocks_y: usize,
+    },
+}
+
+impl From<LevelMap> for MapSelection {
+    fn from(map: LevelMap) -> Self {
+        match map {
+            LevelMap::Preset => MapSelection::Preset,
+            LevelMap::City {
+                seed,
+                blocks_x,
+                blocks_y,
+            } => MapSelection::City {
+                seed,
+                blocks_x,
+                blocks_y,
+            },
+        }
+    }
+}
+
+impl MapSelection {
+    /// Name of the map outside of the campaign.
+    pub fn name(&self) -> String {
+        match self {
+            MapSelection::Preset => String::from("Preset map"),
+            MapSelection::Random(seed) | MapSelection::City { seed, .. } => format!("City {seed}"),
+        }
+    }
+
+    /// Name that results of the map are kept under, which all random cities share since
+    /// none of them is played twice.
+    pub fn shared_name(&self) -> String {
+        match self {
+            MapSelection::Random(_) => String::from("Random city"),
+            selection => selection.name(),
+        }
+    }
+
+    /// Identifier that tells the maps apart, as opposed to the name.
+    pub fn id(&self) -> String {
+        match self {
+            MapSelection::Preset => String::from("preset"),
+            MapSelection::Random(seed) => format!("city-{seed}"),
+            MapSelection::City {
+                seed,
+                blocks_x,
+                blocks_y,
+            } => format!("city-{seed}-{blocks_x}x{blocks_y}"),
+        }
+    }
+}
+
+fn setup(
+    commands: Commands,
+    selection: Res<MapSelection>,
+    game_state: ResMut<NextState<GameState>>,
+) {
+    match *selection {
+        MapSelection::Preset => setup_preset(commands, game_state),
+        MapSelection::Random(seed) => setup_random(commands, CityGenerator::new(seed)),
+        MapSelection::City {
+            seed,
+            blocks_x,
+            blocks_y,
+        } => setup_random(
+            commands,
+            CityGenerator::new(seed).with_blocks(blocks_x, blocks_y),
+        ),
+    }
+}
+
+/// Loads the preset map from its files, which are created when they don't exist yet.
+///
+/// Files that can't be played are kept for the user to fix them and the built-in preset map is
+/// played instead of them.
+fn setup_preset(mut commands: Commands, mut game_state: ResMut<NextState<GameState>>) {
+    let (decoration, solid) = match load_preset() {
+        Ok(layers) => layers,
+        Err(err) => {
+            if err.is_missing() {
+                info!("Creating preset map files");
+                if let Err(err) = preset::generate_preset_maps() {
+                    warn!("{err}");
+                }
+            } else {
+                warn!("{err}. Playing built-in preset map instead...");
+            }
+            let decoration = preset::decoration_map();
+            let solid = preset::solid_map();
+            if !validate_layers(&decoration, &solid) {
+                error!("Built-in preset map is invalid");
+                game_state.set(GameState::MainMenu);
+                return;
+            }
+            (decoration, solid)
+        }
+    };
+
+    commands.spawn((decoration, OnGameScreen));
+    commands.spawn((solid, OnGameScreen));
+    for point in preset::delivery_points() {
+        commands.spawn((point, OnGameScreen));
+    }
+}
+
+fn load_preset() -> Result<(MapData<DecorationTile>, MapData<SolidTile>), PresetError> {
+    let decoration = MapData::load(Path::new("decoration.beer_map"))?;
+    let solid = MapData::load(Path::new("solid.beer_map"))?;
+    if !validate_layers(&decoration, &solid) {
+        return Err(PresetError::InvalidLayers);
+    }
+    Ok((decoration, solid))
+}
 
-    if let (Ok(map_data1), Ok(map_data2)) = (map_data1, map_data2) {
-        commands.spawn((map_data1, OnGameScreen));
-        commands.spawn((map_data2, OnGameScreen));
-    } else {
-        println!("Invalid map(s). Loading preset...");
-        crate::map::preset::generate_preset_maps().unwrap();
-        setup(commands);
+#[derive(Debug, thiserror::Error)]
+enum PresetError {
+    #[error(transparent)]
+    Load(#[from] MapLoadError),
+    #[error("layers of the preset map don't match each other")]
+    InvalidLayers,
+}
+
+impl PresetError {
+    fn is_missing(&self) -> bool {
+        matches!(
+            self,
+            PresetError::Load(MapLoadError::FileSystemError(err))
+                if err.kind() == std::io::ErrorKind::NotFound
+        )
     }
 }
 
-fn spawn_tilemap<M, const Z: usize>(
+/// Amount of textures in `city_tiles.png`.
+const TEXTURE_COUNT: u32 = 27 * 18;
+
+/// Checks that layers are consistent with each other and the spritesheet.
+fn validate_layers(decoration: &MapData<DecorationTile>, solid: &MapData<SolidTile>) -> bool {
+    let rules = ValidationRules::new()
+        .with_texture_count(TEXTURE_COUNT)
+        .with_size(decoration.size());
+
+    let mut valid = true;
+    for diagnostic in decoration
+        .validate(&rules)
+        .into_iter()
+        .chain(solid.validate(&rules))
+    {
+        if diagnostic.is_error() {
+            warn!("{diagnostic}");
+            valid = false;
+        } else {
+            info!("{diagnostic}");
+        }
+    }
+    valid
+}
+
+fn setup_random(mut commands: Commands, generator: CityGenerator) {
+    let city = generator.generate();
+    commands.spawn((city.decoration, OnGameScreen));
+    commands.spawn((city.solid, OnGameScreen));
+    for point in city.delivery_points {
+        commands.spawn((point, OnGameScreen));
+    }
+}
+
+/// Depth at which layer of tiles is drawn.
+trait LayerDepth {
+    const DEPTH: f32;
+}
+
+impl LayerDepth for DecorationTile {
+    const DEPTH: f32 = depth::GROUND;
+}
+
+impl LayerDepth for SolidTile {
+    const DEPTH: f32 = depth::BUILDING_BASE;
+}
+
+fn spawn_tilemap<M>(
     query: Query<&MapData<M>, Added<OnGameScreen>>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
 ) where
-    M: TileClass,
+    M: TileClass + LayerDepth,
 {
     for map in query.iter() {
-        let texture_handle: Handle<Image> = asset_server.load("city_tiles.png");
-        let tilemap_entity = commands.spawn_empty().id();
-        let mut tile_storage = TileStorage::empty(map.size());
-
-        for (y, row) in map.iter().enumerate() {
-            for (x, cell) in row.iter().enumerate() {
-                if let Some(cell) = cell {
-                    let tile_pos = TilePos {
-                        x: x as u32,
-                        y: y as u32,
-                    };
-                    let tile_entity = commands
-                        .spawn(TileBundle {
+        spawn_layer(&mut commands, &asset_server, map, M::DEPTH, |tile| {
+            !tile.overhang()
+        });
+        if map.tiles().any(|(_, tile)| tile.is_some_and(M::overhang)) {
+            spawn_layer(
+                &mut commands,
+                &asset_server,
+                map,
+                depth::OVERHANG,
+                M::overhang,
+            );
+        }
+    }
+}
+
+/// Spawns tilemap that consists of map tiles that match the filter.
+fn spawn_layer<M>(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    map: &MapData<M>,
+    z: f32,
+    filter: impl Fn(&M) -> bool,
+) where
+    M: TileClass,
+{
+    let texture_handle: Handle<Image> = asset_server.load("city_tiles.png");
+    let tilemap_entity = commands.spawn(OnGameScreen).id();
+    let mut tile_storage = TileStorage::empty(map.size());
+
+    for (y, row) in map.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if let Some(cell) = cell.as_ref().filter(|cell| filter(cell)) {
+                let tile_pos = TilePos {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                let tile_entity = commands
+                    .spawn((
+                        TileBundle {
                             position: tile_pos,
                             tilemap_id: TilemapId(tilemap_entity),
                             texture_index: TileTextureIndex(cell.texture()),
                             ..Default::default()
-                        })
-                        .id();
-                    tile_storage.set(&tile_pos, tile_entity);
-                }
+                        },
+                        OnGameScreen,
+                    ))
+                    .id();
+                tile_storage.set(&tile_pos, tile_entity);
             }
         }
-
-        let tile_size = TilemapTileSize { x: 16.0, y: 16.0 };
-        let grid_size = tile_size.into();
-        let map_type = TilemapType::default();
-
-        commands.entity(tilemap_entity).insert(TilemapBundle {
-            grid_size,
-            map_type,
-            size: map.size(),
-            storage: tile_storage,
-            texture: TilemapTexture::Single(texture_handle),
-            tile_size,
-            transform: get_tilemap_center_transform(&map.size(), &grid_size, &map_type, Z as f32),
-            ..Default::default()
-        });
     }
+
+    let tile_size = TilemapTileSize { x: 16.0, y: 16.0 };
+    let grid_size = tile_size.into();
+    let map_type = TilemapType::default();
+
+    commands.entity(tilemap_entity).insert(TilemapBundle {
+        grid_size,
+        map_type,
+        size: map.size(),
+        storage: tile_storage,
+        texture: TilemapTexture::Single(texture_handle),
+        tile_size,
+        transform: get_tilemap_center_transform(&map.size(), &grid_size, &map_type, z),
+        ..Default::default()
+    });
 }
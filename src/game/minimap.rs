@@ -0,0 +1,378 @@
+//! Minimap in the corner of the screen and the full city map.
+//!
+//! Both maps show the same texture with one pixel for every tile, which is rendered from the
+//! map layers and rendered again whenever they change. Markers of the couriers and the active
+//! orders are placed over the texture. Full map is toggled with [MAP_KEY], dragged with the
+//! mouse and zoomed with the wheel.
+
+use bevy::{
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    map::{
+        delivery::{DeliveryPoint, DeliveryPointKind},
+        tile::{DecorationTile, SolidTile, TileClass},
+        MapData,
+    },
+    GameState,
+};
+
+use super::{
+    map::world_to_tile,
+    order::ActiveOrders,
+    player::{Player, MAX_PLAYERS},
+    GameSystemSet, OnGameScreen,
+};
+
+const MAP_KEY: KeyCode = KeyCode::M;
+/// Length of the longer side of the minimap in pixels.
+const MINIMAP_SIZE: f32 = 160.;
+/// Length of the longer side of the full map without zoom, as part of the screen height.
+const FULL_MAP_SIZE: f32 = 90.;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 8.;
+/// Zoom change for every line of the mouse wheel.
+const ZOOM_STEP: f32 = 1.2;
+const MARKER_SIZE: f32 = 6.;
+
+const BACKGROUND_COLOR: [u8; 4] = [20, 20, 28, 255];
+const GROUND_COLOR: [u8; 4] = [120, 140, 110, 255];
+const ROAD_COLOR: [u8; 4] = [60, 60, 70, 255];
+const BUILDING_COLOR: [u8; 4] = [150, 100, 80, 255];
+const FRAME_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(cleanup.in_schedule(OnExit(GameState::Game)))
+            .add_systems(
+                (
+                    spawn_maps,
+                    render_map,
+                    toggle_full_map,
+                    pan_and_zoom,
+                    update_markers,
+                )
+                    .chain()
+                    .in_set(GameSystemSet),
+            );
+    }
+}
+
+/// Texture of the city shared by the minimap and the full map.
+#[derive(Resource)]
+struct CityMapImage(Handle<Image>);
+
+/// Full-screen overlay with the full map.
+#[derive(Component)]
+struct FullMap;
+
+/// Pan and zoom of the full map.
+#[derive(Component)]
+struct MapView {
+    /// Size of the map without zoom.
+    size: Vec2,
+    zoom: f32,
+    /// Offset of the map center from the screen center in pixels.
+    pan: Vec2,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    /// Courier of the local player with provided index.
+    Player(usize),
+    /// Pickup of the order of the team with provided index.
+    Pickup(usize),
+    /// Dropoff of the order of the team with provided index.
+    Dropoff(usize),
+}
+
+impl Marker {
+    fn color(&self) -> Color {
+        match self {
+            Marker::Player(0) => Color::WHITE,
+            Marker::Player(_) => Color::rgb(0.4, 0.9, 1.0),
+            Marker::Pickup(_) => Color::rgb(1.0, 0.85, 0.2),
+            Marker::Dropoff(_) => Color::rgb(0.3, 1.0, 0.4),
+        }
+    }
+}
+
+fn door_color(kind: DeliveryPointKind) -> [u8; 4] {
+    match kind {
+        DeliveryPointKind::Brewery => [230, 180, 40, 255],
+        DeliveryPointKind::Bar => [230, 110, 40, 255],
+        DeliveryPointKind::Customer => [80, 150, 240, 255],
+    }
+}
+
+/// Renders one pixel for every tile, top row first.
+fn render(
+    decoration: &MapData<DecorationTile>,
+    solid: &MapData<SolidTile>,
+    points: &[DeliveryPoint],
+) -> Vec<u8> {
+    let (width, height) = (decoration.width(), decoration.height());
+    let mut data = Vec::with_capacity(width * height * 4);
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let color = if let Some(point) = points.iter().find(|p| (p.x, p.y) == (x, y)) {
+                door_color(point.kind)
+            } else if solid.get(x, y).is_some() {
+                BUILDING_COLOR
+            } else {
+                match decoration.get(x, y) {
+                    Some(tile) if tile.road() => ROAD_COLOR,
+                    Some(_) => GROUND_COLOR,
+                    None => BACKGROUND_COLOR,
+                }
+            };
+            data.extend_from_slice(&color);
+        }
+    }
+    data
+}
+
+fn spawn_maps(
+    maps: Query<&MapData<DecorationTile>, Added<OnGameScreen>>,
+    solid: Query<&MapData<SolidTile>>,
+    points: Query<&DeliveryPoint>,
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let (Ok(map), Ok(solid)) = (maps.get_single(), solid.get_single()) else {
+        return;
+    };
+    let points: Vec<_> = points.iter().copied().collect();
+    let image = images.add(Image::new(
+        Extent3d {
+            width: map.width() as u32,
+            height: map.height() as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        render(map, solid, &points),
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+    commands.insert_resource(CityMapImage(image.clone()));
+
+    let aspect = Vec2::new(map.width() as f32, map.height() as f32);
+    let aspect = aspect / aspect.max_element();
+
+    let minimap = aspect * MINIMAP_SIZE;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(10.),
+                        bottom: Val::Px(10.),
+                        ..default()
+                    },
+                    padding: UiRect::all(Val::Px(3.)),
+                    ..default()
+                },
+                background_color: FRAME_COLOR.into(),
+                ..default()
+            },
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(ImageBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(minimap.x), Val::Px(minimap.y)),
+                        ..default()
+                    },
+                    image: image.clone().into(),
+                    ..default()
+                })
+                .with_children(spawn_markers);
+        });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    overflow: Overflow::Hidden,
+                    ..default()
+                },
+                background_color: FRAME_COLOR.into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            FullMap,
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    ImageBundle {
+                        image: image.into(),
+                        ..default()
+                    },
+                    MapView {
+                        size: aspect,
+                        zoom: 1.,
+                        pan: Vec2::ZERO,
+                    },
+                ))
+                .with_children(spawn_markers);
+        });
+}
+
+fn spawn_markers(parent: &mut ChildBuilder) {
+    let markers = [Marker::Pickup, Marker::Dropoff, Marker::Player];
+    for marker in markers
+        .iter()
+        .flat_map(|marker| (0..MAX_PLAYERS).map(marker))
+    {
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(MARKER_SIZE), Val::Px(MARKER_SIZE)),
+                    position_type: PositionType::Absolute,
+                    margin: UiRect {
+                        left: Val::Px(-MARKER_SIZE / 2.),
+                        bottom: Val::Px(-MARKER_SIZE / 2.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                background_color: marker.color().into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            marker,
+        ));
+    }
+}
+
+fn cleanup(mut commands: Commands) {
+    commands.remove_resource::<CityMapImage>();
+}
+
+/// Renders the texture again once any of the layers is changed.
+fn render_map(
+    decoration: Query<Ref<MapData<DecorationTile>>>,
+    solid: Query<Ref<MapData<SolidTile>>>,
+    points: Query<&DeliveryPoint>,
+    image: Option<Res<CityMapImage>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let (Ok(decoration), Ok(solid), Some(image)) =
+        (decoration.get_single(), solid.get_single(), image)
+    else {
+        return;
+    };
+    if image.is_added() || !(decoration.is_changed() || solid.is_changed()) {
+        return;
+    }
+    let Some(image) = images.get_mut(&image.0) else {
+        return;
+    };
+    let points: Vec<_> = points.iter().copied().collect();
+    image.data = render(&decoration, &solid, &points);
+}
+
+fn toggle_full_map(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut full_map: Query<&mut Visibility, With<FullMap>>,
+) {
+    if !keyboard_input.just_pressed(MAP_KEY) {
+        return;
+    }
+    for mut visibility in &mut full_map {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn pan_and_zoom(
+    windows: Query<&Window>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    full_map: Query<&Visibility, With<FullMap>>,
+    mut views: Query<(&mut MapView, &mut Style)>,
+) {
+    let (Ok(window), Ok(visibility)) = (windows.get_single(), full_map.get_single()) else {
+        return;
+    };
+    let drag: Vec2 = motion.iter().map(|motion| motion.delta).sum();
+    let scroll: f32 = wheel.iter().map(|wheel| wheel.y).sum();
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    for (mut view, mut style) in &mut views {
+        if mouse_input.pressed(MouseButton::Left) {
+            view.pan += drag;
+        }
+        if scroll != 0. {
+            let zoom = (view.zoom * ZOOM_STEP.powf(scroll)).clamp(MIN_ZOOM, MAX_ZOOM);
+            // Point in the middle of the screen stays in place.
+            let change = zoom / view.zoom;
+            view.pan *= change;
+            view.zoom = zoom;
+        }
+
+        let size = view.size * window.height() * FULL_MAP_SIZE / 100. * view.zoom;
+        style.size = Size::new(Val::Px(size.x), Val::Px(size.y));
+        style.position = UiRect {
+            left: Val::Px(view.pan.x),
+            top: Val::Px(view.pan.y),
+            ..default()
+        };
+    }
+}
+
+fn update_markers(
+    maps: Query<&MapData<DecorationTile>>,
+    players: Query<(&Transform, &Player)>,
+    orders: Res<ActiveOrders>,
+    mut markers: Query<(&Marker, &mut Style, &mut Visibility)>,
+) {
+    let Ok(map) = maps.get_single() else {
+        return;
+    };
+    for (marker, mut style, mut visibility) in &mut markers {
+        let tile = match *marker {
+            Marker::Player(index) => players
+                .iter()
+                .find(|(_, player)| player.index == index)
+                .and_then(|(transform, _)| {
+                    world_to_tile(map.size(), transform.translation.truncate())
+                }),
+            Marker::Pickup(team) => orders
+                .get(team)
+                .filter(|order| !order.picked_up())
+                .map(|order| (order.pickup.x, order.pickup.y)),
+            Marker::Dropoff(team) => orders
+                .get(team)
+                .map(|order| (order.dropoff.x, order.dropoff.y)),
+        };
+        let Some((x, y)) = tile else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        // Markers are placed at the center of the pixel of their tile.
+        style.position = UiRect {
+            left: Val::Percent((x as f32 + 0.5) / map.width() as f32 * 100.),
+            bottom: Val::Percent((y as f32 + 0.5) / map.height() as f32 * 100.),
+            ..default()
+        };
+    }
+}
@@ -0,0 +1,231 @@
+//! Navigation grid of the loaded map and the GPS arrow.
+//!
+//! [NavGrid] is built once the map is loaded and kept as a resource for NPCs and orders.
+//! Tiles that change in the map layers afterwards are updated in the grid as well.
+//! Parked vehicles make their tiles more expensive, so that traffic drives around them.
+//! Arrow next to every courier points along the route to the target of its team's order.
+
+use bevy::prelude::*;
+
+use crate::{
+    map::{
+        navigation::{Movement, NavGrid, BASE_COST},
+        tile::{DecorationTile, SolidTile},
+        MapData,
+    },
+    GameState,
+};
+
+use super::{
+    depth,
+    map::{tile_to_world, world_to_tile, TILE_SIZE},
+    order::ActiveOrders,
+    player::Player,
+    simulation::SimulationSystems,
+    vehicle::Vehicle,
+    GameSystemSet, OnGameScreen,
+};
+
+/// Extra cost of a tile with a parked vehicle.
+const PARKED_PENALTY: u32 = 20 * BASE_COST;
+/// Distance from the courier at which the arrow is shown.
+const ARROW_DISTANCE: f32 = 14.;
+/// Amount of tiles ahead on the route that the arrow points at.
+const LOOKAHEAD: usize = 3;
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(cleanup.in_schedule(OnExit(GameState::Game)))
+            .add_step_systems((build_grid, mark_parked_vehicles));
+    }
+}
+
+/// Plugin that shows the GPS arrows next to the couriers.
+pub struct GpsArrowPlugin;
+
+impl Plugin for GpsArrowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (spawn_arrows, update_route, point_arrow)
+                .chain()
+                .in_set(GameSystemSet),
+        );
+    }
+}
+
+/// Route from the courier to the target of its team's order.
+#[derive(Component, Debug)]
+struct GpsArrow {
+    courier: Entity,
+    /// Tiles of the route, starting with the one that the courier stands at.
+    route: Vec<(usize, usize)>,
+}
+
+/// Builds the grid once the map is loaded and applies later changes of the map layers to it.
+fn build_grid(
+    grid: Option<ResMut<NavGrid>>,
+    decoration: Query<Ref<MapData<DecorationTile>>>,
+    solid: Query<Ref<MapData<SolidTile>>>,
+    // Layers that the grid was last updated with.
+    mut seen: Local<Option<(MapData<DecorationTile>, MapData<SolidTile>)>>,
+    mut commands: Commands,
+) {
+    let (Ok(decoration), Ok(solid)) = (decoration.get_single(), solid.get_single()) else {
+        return;
+    };
+    if decoration.is_added() {
+        commands.insert_resource(NavGrid::new(&decoration, &solid));
+        *seen = Some((MapData::clone(&decoration), MapData::clone(&solid)));
+        return;
+    }
+
+    let (Some(mut grid), Some((seen_decoration, seen_solid))) = (grid, &mut *seen) else {
+        return;
+    };
+    if decoration.is_changed() {
+        grid.update_decoration(seen_decoration.diff(&decoration));
+        *seen_decoration = MapData::clone(&decoration);
+    }
+    if solid.is_changed() {
+        grid.update_solid(seen_solid.diff(&solid));
+        *seen_solid = MapData::clone(&solid);
+    }
+}
+
+/// Spawns an arrow for every courier once the grid is built.
+fn spawn_arrows(
+    grid: Option<Res<NavGrid>>,
+    players: Query<Entity, With<Player>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    if !grid.is_some_and(|grid| grid.is_added()) {
+        return;
+    }
+    for courier in &players {
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("arrow.png"),
+                visibility: Visibility::Hidden,
+                transform: Transform::from_xyz(0., 0., depth::OVERHANG),
+                ..default()
+            },
+            GpsArrow {
+                courier,
+                route: Vec::new(),
+            },
+            OnGameScreen,
+        ));
+    }
+}
+
+/// Removes the grid, so that it's added anew with the next map.
+fn cleanup(mut commands: Commands) {
+    commands.remove_resource::<NavGrid>();
+}
+
+/// Updates penalties of the tiles that parked vehicles stand at.
+fn mark_parked_vehicles(
+    grid: Option<ResMut<NavGrid>>,
+    maps: Query<&MapData<DecorationTile>>,
+    vehicles: Query<(&Transform, &Vehicle)>,
+    mut marked: Local<Vec<(usize, usize)>>,
+) {
+    let (Some(mut grid), Ok(map)) = (grid, maps.get_single()) else {
+        return;
+    };
+    let mut parked: Vec<_> = vehicles
+        .iter()
+        .filter(|(_, vehicle)| vehicle.is_parked())
+        .filter_map(|(transform, _)| world_to_tile(map.size(), transform.translation.truncate()))
+        .collect();
+    parked.sort_unstable();
+    if grid.is_added() {
+        marked.clear();
+    } else if *marked == parked {
+        return;
+    }
+
+    for &(x, y) in marked.iter() {
+        let _ = grid.set_penalty(x, y, 0);
+    }
+    for &(x, y) in &parked {
+        let _ = grid.set_penalty(x, y, PARKED_PENALTY);
+    }
+    *marked = parked;
+}
+
+/// Finds new route whenever the courier enters another tile or the target changes.
+fn update_route(
+    grid: Option<Res<NavGrid>>,
+    orders: Res<ActiveOrders>,
+    maps: Query<&MapData<DecorationTile>>,
+    players: Query<(&Transform, &Player)>,
+    mut arrows: Query<&mut GpsArrow>,
+) {
+    let (Some(grid), Ok(map)) = (grid, maps.get_single()) else {
+        return;
+    };
+    for mut arrow in &mut arrows {
+        let Ok((transform, player)) = players.get(arrow.courier) else {
+            continue;
+        };
+        let from = world_to_tile(map.size(), transform.translation.truncate());
+        let to = orders
+            .get(player.team)
+            .map(|order| (order.target().x, order.target().y));
+        let (Some(from), Some(to)) = (from, to) else {
+            arrow.route.clear();
+            continue;
+        };
+        if arrow.route.first() == Some(&from) && arrow.route.last() == Some(&to) {
+            continue;
+        }
+        arrow.route = grid
+            .find_path(from, to, Movement::Walking)
+            .unwrap_or_default();
+    }
+}
+
+fn point_arrow(
+    maps: Query<&MapData<DecorationTile>>,
+    players: Query<&Transform, With<Player>>,
+    mut arrows: Query<(&GpsArrow, &mut Transform, &mut Visibility), Without<Player>>,
+) {
+    let Ok(map) = maps.get_single() else {
+        return;
+    };
+    for (arrow, mut transform, mut visibility) in &mut arrows {
+        let Ok(player) = players.get(arrow.courier) else {
+            continue;
+        };
+        point(map, player, arrow, &mut transform, &mut visibility);
+    }
+}
+
+fn point(
+    map: &MapData<DecorationTile>,
+    player: &Transform,
+    arrow: &GpsArrow,
+    transform: &mut Transform,
+    visibility: &mut Visibility,
+) {
+    let position = player.translation.truncate();
+    let direction = arrow
+        .route
+        .get(LOOKAHEAD.min(arrow.route.len().saturating_sub(1)))
+        .map(|&(x, y)| tile_to_world(map.size(), x, y) - position)
+        .filter(|direction| direction.length() > TILE_SIZE);
+    let Some(direction) = direction else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Inherited;
+    let offset = direction.normalize() * ARROW_DISTANCE;
+    transform.translation.x = position.x + offset.x;
+    transform.translation.y = position.y + offset.y;
+    transform.rotation = Quat::from_rotation_z(offset.y.atan2(offset.x));
+}
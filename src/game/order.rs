@@ -0,0 +1,495 @@
+//! Delivery orders.
+//!
+//! Every order requires beer to be picked up at the brewery and brought to a bar or a customer
+//! before the deadline. Customers with better reputation order more often, and bars only order
+//! from couriers that are trusted in the district. Every team of couriers has one order slot,
+//! co-op couriers either share it or compete with their own, as chosen by the [OrderPool].
+//! Orders are taken and given up with [OrderRequest]s, and beer is picked up and delivered by
+//! interacting with the doors of the delivery points. Only the courier that picked the beer up
+//! can deliver it.
+
+use bevy::prelude::*;
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
+
+use crate::{
+    map::{
+        delivery::{DeliveryPoint, DeliveryPointKind},
+        navigation::{Movement, NavGrid},
+        tile::DecorationTile,
+        MapData,
+    },
+    reputation::{self, BAR_SCORE, BULK_ORDER_SCORE},
+    GameState,
+};
+
+use super::{
+    cargo::{BeerItem, Cargo, Carrier},
+    clock::GameClock,
+    economy::{self, Economy, Money, Transaction, TransactionKind},
+    interaction::{Focus, Interactable, Interacted},
+    map::{tile_to_world, TILE_SIZE},
+    player::{Coop, Player},
+    reputation::Standing,
+    simulation::{GameRng, SimulationEvents, SimulationSystems},
+};
+
+/// Distance from the door at which courier is able to interact with it.
+const REACH: f32 = TILE_SIZE;
+/// Part of the order value that has to be paid for beer at the brewery.
+const STOCK_SHARE: f32 = 0.4;
+/// Time given to deliver the order for every tile between pickup and dropoff.
+const SECONDS_PER_TILE: f32 = 1.;
+/// Time given to deliver the order regardless of the distance.
+const BASE_TIME: f32 = 20.;
+/// Time after the deadline after which order is cancelled.
+const CANCEL_AFTER: f32 = 60.;
+const CANCELLATION_FINE: Money = Money::dollars(5);
+
+pub struct OrderPlugin;
+
+impl Plugin for OrderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveOrders>()
+            .init_resource::<TeamEarnings>()
+            // Reputation is neutral when it's not tracked, like in the headless simulation.
+            .init_resource::<Standing>()
+            .add_simulation_event::<OrderEvent>()
+            .add_simulation_event::<OrderRequest>()
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_step_systems((setup_doors, new_order, update_prompts, progress_order));
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub pickup: DeliveryPoint,
+    pub dropoff: DeliveryPoint,
+    /// Beer requested by the recipient.
+    pub items: Vec<(BeerItem, u32)>,
+    /// Length of the walking route between pickup and dropoff in tiles.
+    pub distance: f32,
+    /// Elapsed time by which the order has to be delivered.
+    pub deadline: f32,
+    /// Courier that picked up the beer and carries it.
+    pub courier: Option<Entity>,
+}
+
+impl Order {
+    /// Price that the recipient pays for the beer itself.
+    pub fn value(&self) -> Money {
+        price(&self.items)
+    }
+
+    /// Total weight of the beer in kilograms.
+    pub fn weight(&self) -> f32 {
+        self.items
+            .iter()
+            .map(|&(item, count)| item.weight() * count as f32)
+            .sum()
+    }
+
+    pub fn stock_price(&self) -> Money {
+        self.value() * STOCK_SHARE
+    }
+
+    /// Estimates distance between delivery points in tiles.
+    ///
+    /// Route through the streets is used when it's known, otherwise it's the straight line.
+    pub fn estimate_distance(
+        grid: Option<&NavGrid>,
+        from: &DeliveryPoint,
+        to: &DeliveryPoint,
+    ) -> f32 {
+        grid.and_then(|grid| grid.find_path((from.x, from.y), (to.x, to.y), Movement::Walking))
+            .map(|path| (path.len() - 1) as f32)
+            .unwrap_or_else(|| {
+                Vec2::new(from.x as f32, from.y as f32)
+                    .distance(Vec2::new(to.x as f32, to.y as f32))
+            })
+    }
+
+    pub fn picked_up(&self) -> bool {
+        self.courier.is_some()
+    }
+
+    /// Returns location the courier has to go to next.
+    pub fn target(&self) -> &DeliveryPoint {
+        if self.picked_up() {
+            &self.dropoff
+        } else {
+            &self.pickup
+        }
+    }
+}
+
+/// Lists provided items, like "2 kegs, 1 crate".
+fn describe(items: &[(BeerItem, u32)]) -> String {
+    items
+        .iter()
+        .map(|&(item, count)| format!("{count} {}", item.name(count)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns total price of provided items.
+fn price(items: &[(BeerItem, u32)]) -> Money {
+    items.iter().fold(Money::default(), |sum, &(item, count)| {
+        sum + item.price() * count as f32
+    })
+}
+
+/// How the orders are split between the co-op couriers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderPool {
+    /// Couriers work on the same order together.
+    Shared,
+    /// Every courier has its own order and earnings are counted separately.
+    Competitive,
+}
+
+/// Order that every team works on right now, if any, by the team index.
+#[derive(Resource, Debug, Default)]
+pub struct ActiveOrders(pub Vec<Option<Order>>);
+
+impl ActiveOrders {
+    pub fn get(&self, team: usize) -> Option<&Order> {
+        self.0.get(team).and_then(Option::as_ref)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Order> {
+        self.0.iter().flatten()
+    }
+}
+
+/// Money earned with the deliveries by every team, by the team index.
+#[derive(Resource, Debug, Default)]
+pub struct TeamEarnings(pub Vec<Money>);
+
+/// Teams that asked for a new order, which is given once there is a place to deliver to.
+#[derive(Resource, Debug, Default)]
+struct RequestedOrders(Vec<bool>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderEvent {
+    Accepted,
+    PickedUp,
+    Delivered {
+        payout: Money,
+        dropoff: DeliveryPoint,
+        /// Whether the order was delivered after the deadline.
+        late: bool,
+        /// Amount of items that broke on the way.
+        broken: u32,
+    },
+    Cancelled {
+        dropoff: DeliveryPoint,
+    },
+}
+
+/// Sent to take a new order or to give up the active one of the team with provided index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderRequest {
+    New(usize),
+    Cancel(usize),
+}
+
+fn setup(mut commands: Commands, coop: Res<Coop>) {
+    commands.insert_resource(ActiveOrders(vec![None; coop.teams()]));
+    commands.insert_resource(TeamEarnings(vec![Money::default(); coop.teams()]));
+    commands.insert_resource(RequestedOrders(vec![false; coop.teams()]));
+}
+
+/// Places doors of the delivery points on the map.
+fn setup_doors(
+    points: Query<(Entity, &DeliveryPoint), Added<DeliveryPoint>>,
+    maps: Query<&MapData<DecorationTile>>,
+    mut commands: Commands,
+) {
+    let Ok(map) = maps.get_single() else {
+        return;
+    };
+    for (entity, point) in &points {
+        let position = tile_to_world(map.size(), point.x, point.y);
+        commands.entity(entity).insert((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+            // Doors are preferred over customers and vehicles standing next to them.
+            Interactable::new(REACH).with_priority(1),
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn new_order(
+    mut active: ResMut<ActiveOrders>,
+    points: Query<&DeliveryPoint>,
+    time: Res<Time>,
+    clock: Res<GameClock>,
+    grid: Option<Res<NavGrid>>,
+    standing: Res<Standing>,
+    mut requests: EventReader<OrderRequest>,
+    mut requested: ResMut<RequestedOrders>,
+    mut rng: ResMut<GameRng>,
+    mut events: EventWriter<OrderEvent>,
+) {
+    let requested = &mut requested.0;
+    for request in requests.iter() {
+        if let &OrderRequest::New(team) = request {
+            if let Some(requested) = requested.get_mut(team) {
+                *requested = true;
+            }
+        }
+    }
+    for team in 0..active.0.len() {
+        if active.0[team].is_some() || !requested.get(team).copied().unwrap_or_default() {
+            continue;
+        }
+        let Some(order) = generate(
+            &mut rng,
+            &active,
+            &points,
+            &time,
+            &clock,
+            grid.as_deref(),
+            &standing,
+        ) else {
+            continue;
+        };
+        active.0[team] = Some(order);
+        requested[team] = false;
+        events.send(OrderEvent::Accepted);
+    }
+}
+
+/// Picks an order from the places that are open right now, if there are any.
+fn generate(
+    rng: &mut GameRng,
+    active: &ActiveOrders,
+    points: &Query<&DeliveryPoint>,
+    time: &Time,
+    clock: &GameClock,
+    grid: Option<&NavGrid>,
+    standing: &Standing,
+) -> Option<Order> {
+    // Orders only come from places that are open right now.
+    let open: Vec<_> = points
+        .iter()
+        .filter(|point| clock.is_open(point.kind))
+        .collect();
+    let pickup = open
+        .iter()
+        .filter(|point| point.kind == DeliveryPointKind::Brewery)
+        .choose(&mut **rng);
+    let trusted = standing.reputation.score >= BAR_SCORE;
+    let recipients: Vec<_> = open
+        .iter()
+        .filter(|point| match point.kind {
+            DeliveryPointKind::Brewery => false,
+            DeliveryPointKind::Bar => trusted,
+            DeliveryPointKind::Customer => true,
+        })
+        // Competing couriers never deliver to the same place at once.
+        .filter(|point| active.iter().all(|order| order.dropoff != ***point))
+        .collect();
+    let dropoff = recipients
+        .choose_weighted(&mut **rng, |point| {
+            reputation::order_weight(standing.customer(point))
+        })
+        .ok();
+    let (Some(&&pickup), Some(&&&dropoff)) = (pickup, dropoff) else {
+        return None;
+    };
+
+    let items = match dropoff.kind {
+        // Loyal bars always take the largest order that still fits on the hand truck.
+        DeliveryPointKind::Bar if standing.customer(&dropoff) >= BULK_ORDER_SCORE => {
+            vec![(BeerItem::Keg, 2), (BeerItem::Crate, 1)]
+        }
+        DeliveryPointKind::Bar => vec![
+            (BeerItem::Keg, rng.gen_range(1..=2)),
+            (BeerItem::Crate, rng.gen_range(0..=1)),
+        ],
+        _ if rng.gen_bool(0.5) => vec![(BeerItem::Bottle, rng.gen_range(6..=12))],
+        _ => vec![(BeerItem::Crate, rng.gen_range(1..=2))],
+    };
+    let distance = Order::estimate_distance(grid, &pickup, &dropoff);
+    Some(Order {
+        pickup,
+        dropoff,
+        items: items.into_iter().filter(|&(_, count)| count > 0).collect(),
+        distance,
+        deadline: time.elapsed_seconds() + BASE_TIME + distance * SECONDS_PER_TILE,
+        courier: None,
+    })
+}
+
+/// Offers to pick up or deliver the beer at the doors that the orders lead to.
+///
+/// Prompts are updated when the orders change, as well as when new doors become interactable
+/// or couriers focus something else, so that no door is left without its prompt.
+fn update_prompts(
+    active: Res<ActiveOrders>,
+    mut doors: Query<(&DeliveryPoint, &mut Interactable)>,
+    focus: Query<(), Changed<Focus>>,
+) {
+    let new_doors = doors
+        .iter_mut()
+        .any(|(_, interactable)| interactable.is_added());
+    if !active.is_changed() && !new_doors && focus.is_empty() {
+        return;
+    }
+    for (point, mut interactable) in &mut doors {
+        let prompts: Vec<_> = active
+            .iter()
+            .filter(|order| order.target() == point)
+            .map(|order| {
+                let action = if order.picked_up() {
+                    "Deliver"
+                } else {
+                    "Pick up"
+                };
+                format!("{action} {}", describe(&order.items))
+            })
+            .collect();
+        interactable.prompt = (!prompts.is_empty()).then(|| prompts.join(" / "));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn progress_order(
+    mut active: ResMut<ActiveOrders>,
+    mut earnings: ResMut<TeamEarnings>,
+    mut players: Query<(&Player, &mut Cargo)>,
+    mut interactions: EventReader<Interacted>,
+    mut requests: EventReader<OrderRequest>,
+    doors: Query<&DeliveryPoint>,
+    time: Res<Time>,
+    economy: Res<Economy>,
+    mut transactions: EventWriter<Transaction>,
+    mut events: EventWriter<OrderEvent>,
+) {
+    let now = time.elapsed_seconds();
+
+    let given_up: Vec<_> = requests
+        .iter()
+        .filter_map(|request| match *request {
+            OrderRequest::Cancel(team) => Some(team),
+            OrderRequest::New(_) => None,
+        })
+        .collect();
+    for (team, slot) in active.0.iter_mut().enumerate() {
+        let Some(order) = slot else {
+            continue;
+        };
+        if given_up.contains(&team) || now > order.deadline + CANCEL_AFTER {
+            // Beer goes back to the brewery.
+            if let Some(Ok((_, mut cargo))) = order.courier.map(|courier| players.get_mut(courier))
+            {
+                unload_order(&mut cargo, &order.items);
+            }
+            transactions.send(Transaction::expense(
+                TransactionKind::Fine,
+                CANCELLATION_FINE,
+            ));
+            events.send(OrderEvent::Cancelled {
+                dropoff: order.dropoff,
+            });
+            *slot = None;
+        }
+    }
+
+    for &Interacted { target, courier } in interactions.iter() {
+        let Ok((player, mut cargo)) = players.get_mut(courier) else {
+            continue;
+        };
+        let Some(slot) = active.0.get_mut(player.team) else {
+            continue;
+        };
+        let Some(order) = slot else {
+            continue;
+        };
+        if !doors.get(target).is_ok_and(|door| door == order.target()) {
+            continue;
+        }
+
+        match order.courier {
+            None => {
+                if !economy.can_afford(order.stock_price()) {
+                    continue;
+                }
+                let weight = cargo.weight() + order.weight();
+                if cargo.carrier().is_vehicle() {
+                    if weight > cargo.carrier().capacity() {
+                        continue;
+                    }
+                } else {
+                    // Brewery lends the smallest carrier that fits the whole order.
+                    let Some(carrier) = Carrier::for_weight(weight) else {
+                        continue;
+                    };
+                    if cargo.set_carrier(carrier).is_err() {
+                        continue;
+                    }
+                }
+                for &(item, count) in &order.items {
+                    // Carrier is large enough for the whole order.
+                    let _ = cargo.load(item, count);
+                }
+                order.courier = Some(courier);
+                transactions.send(Transaction::expense(
+                    TransactionKind::Stock,
+                    order.stock_price(),
+                ));
+                events.send(OrderEvent::PickedUp);
+            }
+            Some(carrier) if carrier == courier => {
+                // Some of the beer may have broken on the way, recipient only pays for the rest.
+                let delivered = unload_order(&mut cargo, &order.items);
+                let share = if order.value() == Money::default() {
+                    1.
+                } else {
+                    price(&delivered) / order.value()
+                };
+                let broken = order
+                    .items
+                    .iter()
+                    .zip(&delivered)
+                    .map(|(&(_, ordered), &(_, delivered))| ordered - delivered)
+                    .sum();
+                let payout =
+                    economy::payout(order.value(), order.distance, now - order.deadline) * share;
+                transactions.send(Transaction::income(TransactionKind::Delivery, payout));
+                if let Some(earned) = earnings.0.get_mut(player.team) {
+                    *earned += payout;
+                }
+                events.send(OrderEvent::Delivered {
+                    payout,
+                    dropoff: order.dropoff,
+                    late: now > order.deadline,
+                    broken,
+                });
+                *slot = None;
+            }
+            // Only the courier carrying the beer can deliver it.
+            Some(_) => {}
+        }
+    }
+}
+
+/// Takes items of the order out of the cargo and returns how many of them were still there.
+///
+/// Courier on foot keeps the smallest carrier that holds the rest of the cargo.
+fn unload_order(cargo: &mut Cargo, items: &[(BeerItem, u32)]) -> Vec<(BeerItem, u32)> {
+    let unloaded = items
+        .iter()
+        .map(|&(item, count)| (item, cargo.unload(item, count)))
+        .collect();
+    if !cargo.carrier().is_vehicle() {
+        if let Some(carrier) = Carrier::for_weight(cargo.weight()) {
+            let _ = cargo.set_carrier(carrier);
+        }
+    }
+    unloaded
+}
@@ -1,148 +1,448 @@
+//! Couriers controlled by the local players.
+//!
+//! There is one courier, or two in the local co-op chosen with [Coop]. Every courier has its
+//! own [Controls] and is followed by the camera with the same index. Couriers move in the
+//! simulation steps according to the actions of their players in [StepInput].
+
 use bevy::{prelude::*, transform::TransformSystem};
 
-use crate::GameState;
+use crate::{
+    map::{
+        navigation::{Movement, NavGrid},
+        tile::SolidTile,
+        MapData,
+    },
+    replay::Actions,
+    GameState,
+};
+
+use super::{
+    camera::PlayerCamera,
+    cargo::{Cargo, Shock},
+    depth::{self, YSort},
+    dialogue::in_dialogue,
+    input::StepInput,
+    interaction::Focus,
+    map::{is_solid, tile_to_world, world_to_tile, TILE_SIZE},
+    order::OrderPool,
+    simulation::SimulationSystems,
+    traffic::Obstacle,
+    vehicle::Driving,
+    GameSystemSet, OnGameScreen,
+};
 
-use super::GameSystemSet;
+/// Speed of the courier walking without any cargo.
+const WALK_SPEED: f32 = 50.;
+/// Speed multiplier applied while running.
+const RUN_MULTIPLIER: f32 = 1.8;
+/// Shock applied to the cargo for every second of running.
+const RUN_SHOCK: f32 = 0.2;
+/// Shock applied to the cargo when courier hits a wall at walking speed.
+const COLLISION_SHOCK: f32 = 0.5;
+/// Shock applied to the cargo when courier stops running at once.
+const STOP_SHOCK: f32 = 0.4;
+/// Half size of the area around courier's feet that collides with solid tiles.
+const HITBOX: Vec2 = Vec2::new(5., 3.);
+/// Offset of the courier's feet from the center of the sprite.
+pub const FEET_OFFSET: Vec2 = Vec2::new(0., -5.);
+/// Largest amount of local players.
+pub const MAX_PLAYERS: usize = 2;
+/// Characters of `city_tiles.png` that play the couriers.
+const CHARACTERS: [usize; MAX_PLAYERS] = [0, 2];
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(setup.in_schedule(OnEnter(GameState::Game)))
+        app.init_resource::<Coop>()
+            .add_event::<Footstep>()
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
             .add_systems(
                 (
-                    movement.in_base_set(CoreSet::FixedUpdate),
-                    animate_player,
+                    animate_player.run_if(not(in_dialogue)),
                     follow_player
                         .in_base_set(CoreSet::PostUpdate)
                         .before(TransformSystem::TransformPropagate),
                 )
                     .in_set(GameSystemSet),
-            );
+            )
+            .add_step_system(place_couriers.before(movement))
+            .add_step_system(movement.run_if(not(in_dialogue)));
     }
 }
 
-#[derive(Component)]
-pub struct Player;
+/// Plugin that draws the couriers.
+pub struct CourierSpritePlugin;
 
-#[derive(Component, Deref, DerefMut)]
-struct AnimationTimer(Timer);
+impl Plugin for CourierSpritePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(add_sprites.in_set(GameSystemSet));
+    }
+}
 
-pub fn movement(
-    time: Res<Time>,
-    keyboard_input: Res<Input<KeyCode>>,
-    mut player: Query<&mut Transform, With<Player>>,
-) {
-    let mut transform = player.single_mut();
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Player {
+    /// Index of the local player, starting from 0.
+    pub index: usize,
+    /// Index of the order slot that the courier works on, which is shared in co-op.
+    pub team: usize,
+}
+
+/// Local co-op of two couriers with provided order pool, or `None` for a single courier.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct Coop(pub Option<OrderPool>);
+
+impl Coop {
+    pub fn players(&self) -> usize {
+        match self.0 {
+            Some(_) => MAX_PLAYERS,
+            None => 1,
+        }
+    }
+
+    /// Amount of couriers or groups of couriers that have their own order.
+    pub fn teams(&self) -> usize {
+        match self.0 {
+            Some(OrderPool::Competitive) => 2,
+            Some(OrderPool::Shared) | None => 1,
+        }
+    }
+}
+
+/// Keys that the courier is controlled with.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Controls {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub run: KeyCode,
+    pub interact: KeyCode,
+}
 
-    let mut direction = Vec3::ZERO;
-    if keyboard_input.pressed(KeyCode::A) {
-        direction += Vec3::NEG_X;
+impl Controls {
+    pub const FIRST: Controls = Controls {
+        up: KeyCode::W,
+        down: KeyCode::S,
+        left: KeyCode::A,
+        right: KeyCode::D,
+        run: KeyCode::LShift,
+        interact: KeyCode::E,
+    };
+    pub const SECOND: Controls = Controls {
+        up: KeyCode::Up,
+        down: KeyCode::Down,
+        left: KeyCode::Left,
+        right: KeyCode::Right,
+        run: KeyCode::RShift,
+        interact: KeyCode::RControl,
+    };
+
+    pub fn for_player(index: usize) -> Self {
+        match index {
+            0 => Self::FIRST,
+            _ => Self::SECOND,
+        }
     }
-    if keyboard_input.pressed(KeyCode::D) {
-        direction += Vec3::X;
+
+    /// Returns actions of the pressed keys.
+    pub fn actions(&self, keyboard_input: &Input<KeyCode>) -> Actions {
+        [
+            (self.up, Actions::UP),
+            (self.down, Actions::DOWN),
+            (self.left, Actions::LEFT),
+            (self.right, Actions::RIGHT),
+            (self.run, Actions::RUN),
+            (self.interact, Actions::INTERACT),
+        ]
+        .into_iter()
+        .filter(|&(key, _)| keyboard_input.pressed(key))
+        .fold(Actions::NONE, |actions, (_, action)| actions | action)
     }
-    if keyboard_input.pressed(KeyCode::W) {
-        direction += Vec3::Y;
+}
+
+/// Returns normalized direction of the actions, or zero if courier doesn't move.
+pub fn direction(actions: Actions) -> Vec2 {
+    let mut direction = Vec2::ZERO;
+    if actions.contains(Actions::LEFT) {
+        direction += Vec2::NEG_X;
+    }
+    if actions.contains(Actions::RIGHT) {
+        direction += Vec2::X;
     }
-    if keyboard_input.pressed(KeyCode::S) {
-        direction += Vec3::NEG_Y;
+    if actions.contains(Actions::UP) {
+        direction += Vec2::Y;
     }
-    if direction != Vec3::ZERO {
-        direction = direction.normalize()
+    if actions.contains(Actions::DOWN) {
+        direction += Vec2::NEG_Y;
+    }
+    direction.normalize_or_zero()
+}
+
+#[derive(Component)]
+struct AnimationTimer {
+    timer: Timer,
+    /// Frame of the walking animation.
+    offset: usize,
+    /// Index of the character in `city_tiles.png`.
+    character: usize,
+}
+
+/// Velocity of the courier during the last movement step.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub struct Velocity(Vec2);
+
+/// Sent when a foot of the walking courier touches the ground, with position of the feet.
+#[derive(Debug, Clone, Copy)]
+pub struct Footstep(pub Vec2);
+
+pub fn movement(
+    time: Res<Time>,
+    input: Res<StepInput>,
+    mut players: Query<(Entity, &mut Transform, &mut Velocity, &Cargo, &Player), Without<Driving>>,
+    maps: Query<&MapData<SolidTile>>,
+    obstacles: Query<(&Transform, &Obstacle), Without<Player>>,
+    mut shocks: EventWriter<Shock>,
+) {
+    for (courier, mut transform, mut velocity, cargo, player) in &mut players {
+        let actions = input.pressed(player.index);
+        let direction = direction(actions);
+        let walk_speed = WALK_SPEED * cargo.speed();
+        let running = actions.contains(Actions::RUN) && direction != Vec2::ZERO;
+        let speed = if running {
+            walk_speed * RUN_MULTIPLIER
+        } else {
+            walk_speed
+        };
+        let previous = **velocity;
+        let mut current = direction * speed;
+
+        let dt = time.delta_seconds();
+        let mut shock = |amount| shocks.send(Shock { courier, amount });
+        if running {
+            shock(RUN_SHOCK * dt);
+        }
+        if previous.length() > walk_speed * 1.1 && current.length() < walk_speed * 0.5 {
+            shock(STOP_SHOCK);
+        }
+
+        // Every axis is resolved separately, so that courier slides along walls.
+        let blocked = |position: Vec2| {
+            maps.iter().any(|map| !walkable(map, position))
+                || obstacles.iter().any(|(obstacle, Obstacle { radius })| {
+                    obstacle.translation.truncate().distance(position) < radius + HITBOX.x
+                })
+        };
+        let mut position = transform.translation.truncate();
+        for axis in [Vec2::X, Vec2::Y] {
+            let step = axis * current.dot(axis) * dt;
+            if step == Vec2::ZERO {
+                continue;
+            }
+            // Courier that got stuck inside a wall is free to walk out of it.
+            if blocked(position + step) && !blocked(position) {
+                // Only the first hit is a shock, pushing against the wall is harmless.
+                let impact = previous.dot(axis).abs();
+                if impact > 0. {
+                    shock(COLLISION_SHOCK * impact / WALK_SPEED);
+                }
+                current -= axis * current.dot(axis);
+            } else {
+                position += step;
+            }
+        }
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        **velocity = current;
     }
+}
 
-    let z = transform.translation.z;
-    transform.translation += time.delta_seconds() * direction * 50.;
-    transform.translation.z = z;
+/// Checks whether courier standing at provided position doesn't collide with solid tiles.
+pub fn walkable(map: &MapData<SolidTile>, position: Vec2) -> bool {
+    let feet = position + FEET_OFFSET;
+    [
+        Vec2::new(-HITBOX.x, -HITBOX.y),
+        Vec2::new(HITBOX.x, -HITBOX.y),
+        Vec2::new(-HITBOX.x, HITBOX.y),
+        Vec2::new(HITBOX.x, HITBOX.y),
+    ]
+    .into_iter()
+    .all(|corner| !is_solid(map, feet + corner))
 }
 
 fn animate_player(
     time: Res<Time>,
-    keyboard_input: Res<Input<KeyCode>>,
+    input: Res<StepInput>,
     mut query: Query<
         (
+            &Transform,
+            &Player,
             &mut MovementAnimation,
             &mut AnimationTimer,
             &mut TextureAtlasSprite,
         ),
-        With<Player>,
+        Without<Driving>,
     >,
-    mut offset: Local<usize>,
+    mut footsteps: EventWriter<Footstep>,
 ) {
-    let (mut animation, mut timer, mut sprite) = query.single_mut();
-
-    let new_animation;
-    if keyboard_input.pressed(KeyCode::S) {
-        new_animation = MovementAnimation::Walking(Direction::Down);
-    } else if keyboard_input.pressed(KeyCode::W) {
-        new_animation = MovementAnimation::Walking(Direction::Up);
-    } else if keyboard_input.pressed(KeyCode::D) {
-        new_animation = MovementAnimation::Walking(Direction::Right);
-    } else if keyboard_input.pressed(KeyCode::A) {
-        new_animation = MovementAnimation::Walking(Direction::Left);
-    } else {
-        *offset = 0;
-        new_animation = MovementAnimation::Standing(animation.direction());
-    }
-
-    let mut should_change = false;
-    timer.tick(time.delta());
-    if animation.ne(&new_animation) {
-        *animation = new_animation;
-        should_change = true;
-        timer.reset();
-    }
-    should_change |= timer.just_finished();
-
-    if should_change {
-        sprite.index = animation.index(*offset);
-        if *offset < animation.animation_len() {
-            *offset += 1;
+    for (transform, player, mut animation, mut timer, mut sprite) in &mut query {
+        let actions = input.pressed(player.index);
+        let new_animation;
+        if actions.contains(Actions::DOWN) {
+            new_animation = MovementAnimation::Walking(Direction::Down);
+        } else if actions.contains(Actions::UP) {
+            new_animation = MovementAnimation::Walking(Direction::Up);
+        } else if actions.contains(Actions::RIGHT) {
+            new_animation = MovementAnimation::Walking(Direction::Right);
+        } else if actions.contains(Actions::LEFT) {
+            new_animation = MovementAnimation::Walking(Direction::Left);
         } else {
-            *offset = 0;
+            timer.offset = 0;
+            new_animation = MovementAnimation::Standing(animation.direction());
+        }
+
+        let mut should_change = false;
+        timer.timer.tick(time.delta());
+        if animation.ne(&new_animation) {
+            *animation = new_animation;
+            should_change = true;
+            timer.timer.reset();
+        }
+        should_change |= timer.timer.just_finished();
+
+        if should_change {
+            sprite.index = animation.index(timer.offset) + timer.character * CHARACTER_STRIDE;
+            // Feet touch the ground on every other frame of walking.
+            if matches!(*animation, MovementAnimation::Walking(_)) && timer.offset.is_multiple_of(2)
+            {
+                footsteps.send(Footstep(transform.translation.truncate() + FEET_OFFSET));
+            }
+            timer.offset = (timer.offset + 1) % animation.animation_len();
         }
     }
 }
 
 fn follow_player(
-    mut camera: Query<&mut Transform, With<Camera>>,
-    player: Query<&Transform, (With<Player>, Without<Camera>)>,
+    mut cameras: Query<(&mut Transform, &PlayerCamera)>,
+    players: Query<(&Transform, &Player), Without<PlayerCamera>>,
 ) {
-    let mut camera = camera.single_mut();
-    let player = player.single();
+    for (mut camera, PlayerCamera(index)) in &mut cameras {
+        let Some((player, _)) = players.iter().find(|(_, player)| player.index == *index) else {
+            continue;
+        };
+        let z = camera.translation.z;
+        camera.translation = player.translation;
+        camera.translation.z = z;
+    }
+}
 
-    let z = camera.translation.z;
-    camera.translation = player.translation;
-    camera.translation.z = z;
+fn setup(mut commands: Commands, coop: Res<Coop>) {
+    let movement_animation = MovementAnimation::Standing(Direction::Down);
+    for (index, &character) in CHARACTERS.iter().enumerate().take(coop.players()) {
+        let team = index % coop.teams();
+        // Couriers start next to each other, until they are placed on the map.
+        let position = Vec3::new(index as f32 * TILE_SIZE, 0., depth::SPRITES);
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(position)),
+            Player { index, team },
+            Controls::for_player(index),
+            Focus::default(),
+            Cargo::default(),
+            Velocity::default(),
+            YSort,
+            movement_animation,
+            AnimationTimer {
+                timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+                offset: 0,
+                character,
+            },
+            OnGameScreen,
+        ));
+    }
 }
 
-fn setup(
+/// Moves the couriers to the nearest tiles around the start that they are able to walk on, once
+/// the map is loaded. The first courier stays where it is unless it starts inside a building.
+///
+/// Tiles are picked by the feet of the couriers, just like the collisions.
+fn place_couriers(
+    grid: Option<Res<NavGrid>>,
+    maps: Query<&MapData<SolidTile>>,
+    mut players: Query<(&Player, &mut Transform)>,
+) {
+    let (Some(grid), Ok(map)) = (grid, maps.get_single()) else {
+        return;
+    };
+    if !grid.is_added() {
+        return;
+    }
+    let start = players
+        .iter()
+        .find(|(player, _)| player.index == 0)
+        .map(|(_, transform)| transform.translation.truncate());
+    let Some(start) = start else {
+        return;
+    };
+    let mut couriers: Vec<_> = players.iter_mut().collect();
+    couriers.sort_by_key(|(player, _)| player.index);
+    let mut taken = Vec::new();
+    for (player, mut transform) in couriers {
+        if player.index == 0 && walkable(map, start) {
+            let feet = world_to_tile(map.size(), start + FEET_OFFSET);
+            if let Some((x, y)) = feet.filter(|&(x, y)| grid.is_passable(x, y, Movement::Walking)) {
+                taken.push((x, y));
+                continue;
+            }
+        }
+        let distance =
+            |&(x, y): &(usize, usize)| tile_to_world(map.size(), x, y).distance_squared(start);
+        let nearest = grid
+            .passable(Movement::Walking)
+            .filter(|tile| !taken.contains(tile))
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)));
+        let Some((x, y)) = nearest else {
+            continue;
+        };
+        taken.push((x, y));
+        let position = tile_to_world(map.size(), x, y);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+/// Dresses the new couriers up as their characters.
+fn add_sprites(
+    players: Query<(Entity, &MovementAnimation, &AnimationTimer), Added<Player>>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut atlas: Local<Option<Handle<TextureAtlas>>>,
 ) {
-    let texture_handle = asset_server.load("city_tiles.png");
-    let movement_animation = MovementAnimation::Standing(Direction::Down);
-    let texture_atlas =
-        TextureAtlas::from_grid(texture_handle, Vec2::new(16.0, 16.0), 27, 18, None, None);
-    let texture_atlas_handle = texture_atlases.add(texture_atlas);
-    commands.spawn((
-        SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle,
-            sprite: TextureAtlasSprite::new(movement_animation.index(0)),
-            transform: Transform::from_scale(Vec3::splat(1.))
-                .with_translation(Vec3::new(0., 0., 2.)),
-            ..default()
-        },
-        Player,
-        movement_animation,
-        AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
-    ));
+    for (entity, animation, timer) in &players {
+        let atlas = atlas.get_or_insert_with(|| {
+            texture_atlases.add(TextureAtlas::from_grid(
+                asset_server.load("city_tiles.png"),
+                Vec2::new(16., 16.),
+                27,
+                18,
+                None,
+                None,
+            ))
+        });
+        commands.entity(entity).insert((
+            atlas.clone(),
+            TextureAtlasSprite::new(animation.index(0) + timer.character * CHARACTER_STRIDE),
+        ));
+    }
 }
 
+/// Offset between the frames of different characters in `city_tiles.png`.
+pub const CHARACTER_STRIDE: usize = 3 * 27;
+/// Amount of characters in `city_tiles.png`. The first one is the courier.
+pub const CHARACTER_COUNT: usize = 6;
+
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
-enum MovementAnimation {
+pub enum MovementAnimation {
     Standing(Direction),
     Walking(Direction),
 }
@@ -191,7 +491,7 @@ impl MovementAnimation {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -0,0 +1,259 @@
+//! Recording and playback of the game sessions.
+//!
+//! Every game is recorded and written to [REPLAY_FILE] once it's over, replacing the previous
+//! recording. Replay started from the menu plays the session again with the same settings,
+//! seed and actions of the players, while the keyboard only leaves it with [RETURN_KEY].
+//! Progress is played with the save from the start of the session, and nothing that happens
+//! in the replay is saved.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::{
+    menu::FONT,
+    replay::{Actions, Replay, ReplayCoop, ReplayMap, Session},
+    save::SaveGame,
+    GameState,
+};
+
+use super::{
+    arcade::Arcade,
+    input::{self, StepInput},
+    level::{CurrentLevel, Save, RETURN_KEY},
+    map::MapSelection,
+    order::OrderPool,
+    player::{Coop, MAX_PLAYERS},
+    simulation::{GameRng, StepSet},
+    OnGameScreen,
+};
+
+pub const REPLAY_FILE: &str = "replay.beer_replay";
+const FONT_SIZE: f32 = 24.;
+const TEXT_COLOR: Color = Color::rgb(1.0, 0.4, 0.3);
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_system(cleanup.in_schedule(OnExit(GameState::Game)))
+            .add_systems(
+                (
+                    play.run_if(resource_exists::<Playback>()),
+                    record
+                        .after(input::read_input)
+                        .run_if(resource_exists::<Recording>()),
+                )
+                    .chain()
+                    .in_set(StepSet::Input)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                leave_playback
+                    .run_if(in_state(GameState::Game))
+                    .run_if(resource_exists::<Playback>()),
+            );
+    }
+}
+
+/// Replay that is played instead of the input of the players.
+#[derive(Resource, Debug)]
+pub struct Playback {
+    seed: u64,
+    /// Actions of every player in every step.
+    steps: Vec<[Actions; MAX_PLAYERS]>,
+    /// Index of the next step.
+    next: usize,
+    /// Save of the player, which is put aside while the replay uses the one it was recorded
+    /// with.
+    save: SaveGame,
+}
+
+impl Playback {
+    /// Sets up the session of the replay, which starts once the game is entered.
+    pub fn start(
+        replay: Replay,
+        save: &mut Save,
+        map_selection: &mut MapSelection,
+        current_level: &mut CurrentLevel,
+        arcade: &mut Arcade,
+        coop: &mut Coop,
+    ) -> Self {
+        let session = &replay.session;
+        *map_selection = session.map.into();
+        current_level.0 = session.level.clone();
+        arcade.0 = session.arcade;
+        coop.0 = session.coop.map(OrderPool::from);
+        let save = std::mem::replace(&mut save.0, session.save.clone());
+        let steps = replay
+            .steps()
+            .map(|recorded| {
+                let mut actions = [Actions::NONE; MAX_PLAYERS];
+                for (actions, &recorded) in actions.iter_mut().zip(recorded) {
+                    *actions = recorded;
+                }
+                actions
+            })
+            .collect();
+        Self {
+            seed: replay.seed,
+            steps,
+            next: 0,
+            save,
+        }
+    }
+}
+
+/// Session that is recorded right now.
+#[derive(Resource, Debug)]
+struct Recording(Replay);
+
+#[allow(clippy::too_many_arguments)]
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    playback: Option<Res<Playback>>,
+    map_selection: Res<MapSelection>,
+    current_level: Res<CurrentLevel>,
+    arcade: Res<Arcade>,
+    coop: Res<Coop>,
+    save: Res<Save>,
+) {
+    let Some(playback) = playback else {
+        let seed = rand::random();
+        let session = Session {
+            map: (*map_selection).into(),
+            level: current_level.0.clone(),
+            arcade: arcade.0,
+            coop: coop.0.map(ReplayCoop::from),
+            save: save.0.clone(),
+        };
+        commands.insert_resource(GameRng::new(seed));
+        commands.insert_resource(Recording(Replay::new(session, seed)));
+        return;
+    };
+
+    commands.insert_resource(GameRng::new(playback.seed));
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Replay, {RETURN_KEY:?} to leave"),
+            TextStyle {
+                font: asset_server.load(FONT),
+                font_size: FONT_SIZE,
+                color: TEXT_COLOR,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                right: Val::Px(10.),
+                bottom: Val::Px(10.),
+                ..default()
+            },
+            ..default()
+        }),
+        OnGameScreen,
+    ));
+}
+
+fn cleanup(
+    mut commands: Commands,
+    playback: Option<ResMut<Playback>>,
+    recording: Option<Res<Recording>>,
+    mut save: ResMut<Save>,
+) {
+    if let Some(mut playback) = playback {
+        save.0 = std::mem::take(&mut playback.save);
+        commands.remove_resource::<Playback>();
+    }
+    if let Some(recording) = recording {
+        if let Err(err) = recording.0.save(Path::new(REPLAY_FILE)) {
+            warn!("{err}");
+        }
+        commands.remove_resource::<Recording>();
+    }
+}
+
+/// Replaces actions of the players with the recorded ones, and ends the replay after the last
+/// step.
+fn play(
+    mut playback: ResMut<Playback>,
+    mut input: ResMut<StepInput>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(&actions) = playback.steps.get(playback.next) else {
+        input.advance([Actions::NONE; MAX_PLAYERS]);
+        game_state.set(GameState::MainMenu);
+        return;
+    };
+    input.advance(actions);
+    playback.next += 1;
+}
+
+fn record(input: Res<StepInput>, coop: Res<Coop>, mut recording: ResMut<Recording>) {
+    recording.0.push(&input.actions()[..coop.players()]);
+}
+
+fn leave_playback(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(RETURN_KEY) {
+        game_state.set(GameState::MainMenu);
+    }
+}
+
+impl From<MapSelection> for ReplayMap {
+    fn from(selection: MapSelection) -> Self {
+        match selection {
+            MapSelection::Preset => ReplayMap::Preset,
+            MapSelection::Random(seed) => ReplayMap::Random(seed),
+            MapSelection::City {
+                seed,
+                blocks_x,
+                blocks_y,
+            } => ReplayMap::City {
+                seed,
+                blocks_x,
+                blocks_y,
+            },
+        }
+    }
+}
+
+impl From<ReplayMap> for MapSelection {
+    fn from(map: ReplayMap) -> Self {
+        match map {
+            ReplayMap::Preset => MapSelection::Preset,
+            ReplayMap::Random(seed) => MapSelection::Random(seed),
+            ReplayMap::City {
+                seed,
+                blocks_x,
+                blocks_y,
+            } => MapSelection::City {
+                seed,
+                blocks_x,
+                blocks_y,
+            },
+        }
+    }
+}
+
+impl From<OrderPool> for ReplayCoop {
+    fn from(pool: OrderPool) -> Self {
+        match pool {
+            OrderPool::Shared => ReplayCoop::Shared,
+            OrderPool::Competitive => ReplayCoop::Competitive,
+        }
+    }
+}
+
+impl From<ReplayCoop> for OrderPool {
+    fn from(coop: ReplayCoop) -> Self {
+        match coop {
+            ReplayCoop::Shared => OrderPool::Shared,
+            ReplayCoop::Competitive => OrderPool::Competitive,
+        }
+    }
+}
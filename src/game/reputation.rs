@@ -0,0 +1,115 @@
+//! Reputation of the courier in the district that is played.
+//!
+//! Reputation of the district is taken from the save game when the game starts, changed by the
+//! outcome of every order and saved right away. Outside of the campaign every map counts as a
+//! district of its own, except for the random cities. They share one district, and their
+//! customers aren't saved since none of the cities is played twice.
+
+use bevy::prelude::*;
+
+use crate::{
+    map::delivery::DeliveryPoint,
+    reputation::{DistrictReputation, Outcome},
+    GameState,
+};
+
+use super::{
+    customer,
+    level::{CurrentLevel, Levels, Save},
+    map::MapSelection,
+    order::OrderEvent,
+    replay::Playback,
+    simulation::SimulationSystems,
+};
+
+pub struct ReputationPlugin;
+
+impl Plugin for ReputationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Standing>()
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .add_step_system(
+                record_outcomes
+                    // Tips are decided by the reputation from before the delivery.
+                    .after(customer::react),
+            );
+    }
+}
+
+/// Reputation in the district that is played.
+#[derive(Resource, Debug, Default)]
+pub struct Standing {
+    /// Name that the reputation is saved under.
+    pub district: String,
+    /// Map of the district that customers live on.
+    pub map: String,
+    pub reputation: DistrictReputation,
+}
+
+impl Standing {
+    pub fn customer(&self, point: &DeliveryPoint) -> f32 {
+        self.reputation.customer(&self.map, (point.x, point.y))
+    }
+}
+
+fn district_name(level: &CurrentLevel, levels: &Levels, selection: &MapSelection) -> String {
+    let district = level
+        .0
+        .as_ref()
+        .and_then(|level| levels.0.district_of(&level.id));
+    match district {
+        Some(district) => district.name.clone(),
+        None => selection.shared_name(),
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    level: Res<CurrentLevel>,
+    levels: Res<Levels>,
+    selection: Res<MapSelection>,
+    save: Res<Save>,
+) {
+    let district = district_name(&level, &levels, &selection);
+    commands.insert_resource(Standing {
+        reputation: save.0.reputation.district(&district),
+        district,
+        map: selection.id(),
+    });
+}
+
+fn record_outcomes(
+    mut events: EventReader<OrderEvent>,
+    mut standing: ResMut<Standing>,
+    mut save: ResMut<Save>,
+    selection: Res<MapSelection>,
+    playback: Option<Res<Playback>>,
+) {
+    for event in events.iter() {
+        let (dropoff, outcome) = match *event {
+            OrderEvent::Delivered {
+                dropoff,
+                late,
+                broken,
+                ..
+            } => (dropoff, Outcome::Delivered { late, broken }),
+            OrderEvent::Cancelled { dropoff } => (dropoff, Outcome::Cancelled),
+            OrderEvent::Accepted | OrderEvent::PickedUp => continue,
+        };
+        let Standing {
+            district,
+            map,
+            reputation,
+        } = &mut *standing;
+        reputation.record(map, (dropoff.x, dropoff.y), outcome);
+        let mut saved = reputation.clone();
+        if matches!(*selection, MapSelection::Random(_)) {
+            saved.maps.remove(map.as_str());
+        }
+        save.0.reputation.districts.insert(district.clone(), saved);
+        // Replays don't change the progress.
+        if playback.is_none() {
+            save.write();
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Results screen that is shown at the end of the shift.
+
+use bevy::prelude::*;
+
+use crate::{
+    menu::FONT,
+    score::{Score, Scoring},
+};
+
+use super::{
+    economy::Money,
+    level::{ShiftEnded, RETURN_KEY},
+    simulation::SimulationSystems,
+    OnGameScreen,
+};
+
+const TITLE_SIZE: f32 = 40.;
+const FONT_SIZE: f32 = 24.;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HIGH_SCORE_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_step_system(show_results);
+    }
+}
+
+/// Lines of the score breakdown, with the stat and the points it gave.
+fn breakdown(event: &ShiftEnded) -> Vec<(String, i64)> {
+    let stats = &event.stats;
+    let score = Score::new(stats);
+    if event.scoring == Scoring::Earnings {
+        return vec![(
+            format!("Earnings: {}", Money::cents(stats.earnings)),
+            score.earnings,
+        )];
+    }
+    vec![
+        (
+            format!("Deliveries: {}", stats.deliveries),
+            score.deliveries,
+        ),
+        (
+            format!("On time: {:.0}%", stats.on_time_ratio() * 100.),
+            score.on_time,
+        ),
+        (
+            format!("Earnings: {}", Money::cents(stats.earnings)),
+            score.earnings,
+        ),
+        (format!("Broken items: {}", stats.broken), score.breakage),
+        (
+            format!("Cancelled orders: {}", stats.cancellations),
+            score.cancellations,
+        ),
+    ]
+}
+
+fn show_results(
+    mut shift_ended: EventReader<ShiftEnded>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(event) = shift_ended.iter().last() else {
+        return;
+    };
+    let style = TextStyle {
+        font: asset_server.load(FONT),
+        font_size: FONT_SIZE,
+        color: TEXT_COLOR,
+    };
+    let total = event.scoring.score(&event.stats);
+    let (rank, rank_color) = match event.rank {
+        Some(0) => (String::from("New high score!"), HIGH_SCORE_COLOR),
+        Some(rank) => (
+            format!("#{} on the leaderboard", rank + 1),
+            HIGH_SCORE_COLOR,
+        ),
+        None => (String::from("Not on the leaderboard"), TEXT_COLOR),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.)),
+                        ..default()
+                    },
+                    background_color: PANEL_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!("Shift over: {}", event.name),
+                        TextStyle {
+                            font_size: TITLE_SIZE,
+                            ..style.clone()
+                        },
+                    ));
+                    for (line, points) in breakdown(event) {
+                        parent.spawn(TextBundle::from_section(
+                            format!("{line:<24}{points:>+6}"),
+                            style.clone(),
+                        ));
+                    }
+                    parent.spawn(TextBundle::from_section(
+                        format!("Score: {total}"),
+                        TextStyle {
+                            font_size: TITLE_SIZE,
+                            ..style.clone()
+                        },
+                    ));
+                    parent.spawn(TextBundle::from_section(
+                        rank,
+                        TextStyle {
+                            color: rank_color,
+                            ..style.clone()
+                        },
+                    ));
+                    parent.spawn(TextBundle::from_section(
+                        format!("{RETURN_KEY:?}: back to the menu"),
+                        style.clone(),
+                    ));
+                });
+        });
+}
@@ -0,0 +1,183 @@
+//! Fixed-step simulation of the game.
+//!
+//! Gameplay systems run in [CoreSchedule::FixedUpdate] once for every [STEP] of real time, so
+//! that the game plays out the same way for the same inputs regardless of the frame rate.
+//! During a step [Time] is replaced with the time of the simulation, which starts from zero
+//! with every game and is kept in [SimulationTime] between the steps. Systems of the step run
+//! one after another in the order they were added with [SimulationSystems], events that they
+//! send to each other are updated every step instead of every frame, and [GameRng] is their
+//! only source of randomness.
+
+use std::time::Instant;
+
+use bevy::{ecs::schedule::ExecutorKind, prelude::*};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::GameState;
+
+use super::GameSystemSet;
+
+/// Length of the simulation step in seconds.
+pub const STEP: f32 = 1. / 60.;
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(STEP))
+            .init_resource::<SimulationTime>()
+            .insert_resource(GameRng::new(0))
+            .add_system(setup.in_schedule(OnEnter(GameState::Game)))
+            .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+                schedule
+                    .set_executor_kind(ExecutorKind::SingleThreaded)
+                    .configure_sets(
+                        (StepSet::Begin, StepSet::Input, GameSystemSet, StepSet::End).chain(),
+                    )
+                    .configure_set(StepSet::Begin.run_if(in_state(GameState::Game)))
+                    .configure_set(StepSet::Input.run_if(in_state(GameState::Game)))
+                    .configure_set(GameSystemSet.run_if(in_state(GameState::Game)))
+                    .configure_set(StepSet::End.run_if(in_state(GameState::Game)));
+            })
+            .add_system(
+                begin_step
+                    .in_set(StepSet::Begin)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                end_step
+                    .in_set(StepSet::End)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+    }
+}
+
+/// Parts of the simulation step that run before and after the gameplay systems.
+#[derive(SystemSet, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum StepSet {
+    /// Switches to the simulation time and updates the events.
+    Begin,
+    /// Decides the actions of the players in the step.
+    Input,
+    /// Switches back to the real time.
+    End,
+}
+
+/// Time of the simulation outside of the steps.
+///
+/// Elapsed time of the simulation is the one that deadlines and other gameplay timers are
+/// measured with, so the interface compares them to this one rather than to the real [Time].
+/// During a step the two are swapped.
+#[derive(Resource, Debug, Default)]
+pub struct SimulationTime(pub Time);
+
+/// Random number generator of the gameplay, which is seeded at the start of every game.
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct GameRng(ChaCha8Rng);
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+/// Registers events of the gameplay systems.
+pub trait SimulationEvents {
+    /// Adds event that is updated every simulation step rather than every frame.
+    ///
+    /// Events sent during a step stay around until the end of the next one, so no gameplay
+    /// system misses them no matter how many steps are simulated within a frame.
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self;
+}
+
+impl SimulationEvents for App {
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self {
+        if !self.world.contains_resource::<Events<T>>() {
+            self.init_resource::<Events<T>>().add_system(
+                Events::<T>::update_system
+                    .in_set(StepSet::Begin)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+        }
+        self
+    }
+}
+
+/// Registers systems of the gameplay.
+///
+/// Systems that were added earlier run before the later ones, so that the order of the systems
+/// with conflicting access doesn't change between the games.
+pub trait SimulationSystems {
+    /// Adds system that runs in every simulation step during the game.
+    fn add_step_system<M>(&mut self, system: impl IntoSystemConfig<M>) -> &mut Self;
+
+    /// Adds systems that run in every simulation step during the game, one after another.
+    fn add_step_systems<M>(&mut self, systems: impl IntoSystemConfigs<M>) -> &mut Self;
+}
+
+impl SimulationSystems for App {
+    fn add_step_system<M>(&mut self, system: impl IntoSystemConfig<M>) -> &mut Self {
+        let stage = add_stage(self);
+        self.add_system(system.in_set(stage).in_schedule(CoreSchedule::FixedUpdate))
+    }
+
+    fn add_step_systems<M>(&mut self, systems: impl IntoSystemConfigs<M>) -> &mut Self {
+        let stage = add_stage(self);
+        self.add_systems(
+            systems
+                .chain()
+                .in_set(stage)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+    }
+}
+
+/// Adds stage that runs after the previously added ones.
+fn add_stage(app: &mut App) -> StepStage {
+    let mut stages = app.world.get_resource_or_insert_with(StepStages::default);
+    let stage = StepStage(stages.0);
+    stages.0 += 1;
+    app.edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+        schedule.configure_set(stage.in_set(GameSystemSet));
+        if let Some(previous) = stage.0.checked_sub(1) {
+            schedule.configure_set(stage.after(StepStage(previous)));
+        }
+    });
+    stage
+}
+
+/// Systems added to the simulation step together.
+#[derive(SystemSet, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct StepStage(usize);
+
+/// Amount of [StepStage]s added so far.
+#[derive(Resource, Default)]
+struct StepStages(usize);
+
+fn setup(mut simulation: ResMut<SimulationTime>, mut fixed_time: ResMut<FixedTime>) {
+    let now = Instant::now();
+    simulation.0 = Time::new(now);
+    // Time doesn't advance in its first update, only with the ones after it.
+    simulation.0.update_with_instant(now);
+    *fixed_time = FixedTime::new_from_secs(STEP);
+}
+
+fn begin_step(
+    mut time: ResMut<Time>,
+    mut simulation: ResMut<SimulationTime>,
+    fixed_time: Res<FixedTime>,
+) {
+    let last_step = simulation
+        .0
+        .last_update()
+        .unwrap_or_else(|| simulation.0.startup());
+    simulation
+        .0
+        .update_with_instant(last_step + fixed_time.period);
+    std::mem::swap(&mut *time, &mut simulation.0);
+}
+
+fn end_step(mut time: ResMut<Time>, mut simulation: ResMut<SimulationTime>) {
+    std::mem::swap(&mut *time, &mut simulation.0);
+}
@@ -0,0 +1,297 @@
+//! Pedestrians and cars that wander around the city.
+//!
+//! Every NPC picks a random destination, asks [NavGrid] for the path and follows it.
+//! Pedestrians head to sidewalks and parks and only cross roads on the way, while cars keep
+//! to roads. Both of them are [Obstacle]s for the courier.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::seq::IteratorRandom;
+
+use crate::map::{
+    navigation::{Movement, NavGrid, BASE_COST},
+    tile::DecorationTile,
+    MapData,
+};
+
+use super::{
+    depth::{self, YSort},
+    map::{tile_to_world, world_to_tile},
+    player::{Direction, MovementAnimation, Player, CHARACTER_COUNT, CHARACTER_STRIDE},
+    simulation::{GameRng, SimulationSystems},
+    GameSystemSet, OnGameScreen,
+};
+
+/// Amount of walkable tiles for every pedestrian.
+const TILES_PER_PEDESTRIAN: usize = 40;
+const MAX_PEDESTRIANS: usize = 16;
+/// Amount of road tiles for every car.
+const TILES_PER_CAR: usize = 40;
+const MAX_CARS: usize = 6;
+/// Amount of different cars in `city_tiles.png`.
+const CAR_MODELS: usize = 2;
+const PEDESTRIAN_SPEED: f32 = 25.;
+const CAR_SPEED: f32 = 60.;
+/// Distance in front of the car at which it stops to let the courier pass.
+const BRAKING_DISTANCE: f32 = 20.;
+
+pub struct TrafficPlugin;
+
+impl Plugin for TrafficPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_step_systems((spawn_traffic, plan_routes, follow_routes))
+            .add_systems((animate_pedestrians, animate_cars).in_set(GameSystemSet));
+    }
+}
+
+/// Entity that the courier can't walk or drive through.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Obstacle {
+    pub radius: f32,
+}
+
+/// NPC that walks or drives between random points of the map.
+#[derive(Component, Debug, Clone)]
+struct Wanderer {
+    movement: Movement,
+    speed: f32,
+    /// Tiles left to go through, the next one first.
+    route: VecDeque<(usize, usize)>,
+    facing: Direction,
+}
+
+impl Wanderer {
+    fn new(movement: Movement, speed: f32) -> Self {
+        Self {
+            movement,
+            speed,
+            route: VecDeque::new(),
+            facing: Direction::Down,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Pedestrian {
+    /// Index of the character in `city_tiles.png`.
+    character: usize,
+    frame: usize,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct Car {
+    /// Index of the first sprite of the car in the atlas.
+    model: usize,
+}
+
+/// Populates the city once the navigation grid is built.
+fn spawn_traffic(
+    grid: Option<Res<NavGrid>>,
+    maps: Query<&MapData<DecorationTile>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut rng: ResMut<GameRng>,
+) {
+    let (Some(grid), Ok(map)) = (grid, maps.get_single()) else {
+        return;
+    };
+    if !grid.is_added() {
+        return;
+    }
+
+    let characters = texture_atlases.add(TextureAtlas::from_grid(
+        asset_server.load("city_tiles.png"),
+        Vec2::new(16., 16.),
+        27,
+        18,
+        None,
+        None,
+    ));
+    let walkable = destinations(&grid, Movement::Walking).count();
+    let pedestrians = (walkable / TILES_PER_PEDESTRIAN).min(MAX_PEDESTRIANS);
+    for (x, y) in destinations(&grid, Movement::Walking).choose_multiple(&mut **rng, pedestrians) {
+        let character = 1 + (x + y) % (CHARACTER_COUNT - 1);
+        let position = tile_to_world(map.size(), x, y);
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: characters.clone(),
+                sprite: TextureAtlasSprite::new(
+                    MovementAnimation::Standing(Direction::Down).index(0)
+                        + character * CHARACTER_STRIDE,
+                ),
+                transform: Transform::from_translation(position.extend(depth::SPRITES)),
+                ..default()
+            },
+            Wanderer::new(Movement::Walking, PEDESTRIAN_SPEED),
+            Pedestrian {
+                character,
+                frame: 0,
+                timer: Timer::from_seconds(0.15, TimerMode::Repeating),
+            },
+            Obstacle { radius: 4. },
+            YSort,
+            OnGameScreen,
+        ));
+    }
+
+    let cars = texture_atlases.add(car_atlas(&asset_server));
+    let roads = destinations(&grid, Movement::Driving).count();
+    let amount = (roads / TILES_PER_CAR).min(MAX_CARS);
+    for (i, (x, y)) in destinations(&grid, Movement::Driving)
+        .choose_multiple(&mut **rng, amount)
+        .into_iter()
+        .enumerate()
+    {
+        let position = tile_to_world(map.size(), x, y);
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: cars.clone(),
+                transform: Transform::from_translation(position.extend(depth::SPRITES)),
+                ..default()
+            },
+            Wanderer::new(Movement::Driving, CAR_SPEED),
+            Car {
+                model: i % CAR_MODELS * 3,
+            },
+            Obstacle { radius: 7. },
+            YSort,
+            OnGameScreen,
+        ));
+    }
+}
+
+/// Tiles that NPCs are willing to go to, the ones they'd rather only pass are left out.
+fn destinations(grid: &NavGrid, movement: Movement) -> impl Iterator<Item = (usize, usize)> + '_ {
+    grid.passable(movement)
+        .filter(move |&(x, y)| grid.cost(x, y, movement) == Some(BASE_COST))
+}
+
+/// Cars of `city_tiles.png`, front, back and side sprites of every model in that order.
+fn car_atlas(asset_server: &AssetServer) -> TextureAtlas {
+    let mut atlas = TextureAtlas::new_empty(
+        asset_server.load("city_tiles.png"),
+        Vec2::new(27. * 16., 18. * 16.),
+    );
+    // Taxi and red car.
+    for y in [234., 266.] {
+        for (min_x, max_x) in [(272., 288.), (320., 336.), (244., 268.)] {
+            atlas.add_texture(Rect::new(min_x, y, max_x, y + 22.));
+        }
+    }
+    atlas
+}
+
+/// Picks new destination for NPCs that have arrived.
+fn plan_routes(
+    grid: Option<Res<NavGrid>>,
+    maps: Query<&MapData<DecorationTile>>,
+    mut wanderers: Query<(&Transform, &mut Wanderer)>,
+    mut rng: ResMut<GameRng>,
+) {
+    let (Some(grid), Ok(map)) = (grid, maps.get_single()) else {
+        return;
+    };
+    for (transform, mut wanderer) in &mut wanderers {
+        if !wanderer.route.is_empty() {
+            continue;
+        }
+        let Some(from) = world_to_tile(map.size(), transform.translation.truncate()) else {
+            continue;
+        };
+        let Some(to) = destinations(&grid, wanderer.movement).choose(&mut **rng) else {
+            continue;
+        };
+        if let Some(path) = grid.find_path(from, to, wanderer.movement) {
+            wanderer.route = path.into();
+        }
+    }
+}
+
+fn follow_routes(
+    time: Res<Time>,
+    maps: Query<&MapData<DecorationTile>>,
+    players: Query<&Transform, With<Player>>,
+    mut wanderers: Query<(&mut Transform, &mut Wanderer), Without<Player>>,
+) {
+    let Ok(map) = maps.get_single() else {
+        return;
+    };
+    let players: Vec<_> = players
+        .iter()
+        .map(|player| player.translation.truncate())
+        .collect();
+    for (mut transform, mut wanderer) in &mut wanderers {
+        let Some(&next) = wanderer.route.front() else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+        let target = tile_to_world(map.size(), next.0, next.1);
+        let offset = target - position;
+        let step = wanderer.speed * time.delta_seconds();
+        if offset.length() <= step {
+            transform.translation.x = target.x;
+            transform.translation.y = target.y;
+            wanderer.route.pop_front();
+            continue;
+        }
+
+        let direction = offset.normalize();
+        // Everyone waits for the couriers to get out of the way.
+        let blocked = players.iter().any(|&player| {
+            let ahead = player - position;
+            ahead.length() < BRAKING_DISTANCE && ahead.dot(direction) > 0.
+        });
+        if blocked {
+            continue;
+        }
+        wanderer.facing = if direction.x.abs() > direction.y.abs() {
+            if direction.x > 0. {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if direction.y > 0. {
+            Direction::Up
+        } else {
+            Direction::Down
+        };
+        transform.translation.x += direction.x * step;
+        transform.translation.y += direction.y * step;
+    }
+}
+
+fn animate_pedestrians(
+    time: Res<Time>,
+    mut pedestrians: Query<(&Wanderer, &mut Pedestrian, &mut TextureAtlasSprite)>,
+) {
+    for (wanderer, mut pedestrian, mut sprite) in &mut pedestrians {
+        let animation = if wanderer.route.is_empty() {
+            MovementAnimation::Standing(wanderer.facing)
+        } else {
+            MovementAnimation::Walking(wanderer.facing)
+        };
+        if pedestrian.timer.tick(time.delta()).just_finished() {
+            pedestrian.frame += 1;
+        }
+        let index = animation.index(pedestrian.frame) + pedestrian.character * CHARACTER_STRIDE;
+        if sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
+
+fn animate_cars(mut cars: Query<(&Wanderer, &Car, &mut TextureAtlasSprite), Changed<Wanderer>>) {
+    for (wanderer, car, mut sprite) in &mut cars {
+        let (offset, flip) = match wanderer.facing {
+            Direction::Down => (0, false),
+            Direction::Up => (1, false),
+            Direction::Left => (2, false),
+            Direction::Right => (2, true),
+        };
+        sprite.index = car.model + offset;
+        sprite.flip_x = flip;
+    }
+}
@@ -0,0 +1,589 @@
+//! Vehicles that courier is able to drive.
+//!
+//! Vehicles are parked near the brewery when the map is loaded. Courier gets on and off them
+//! by interacting with them, but only while the vehicle stands still and nobody else drives it.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::prelude::*;
+
+use crate::{
+    campaign,
+    map::{
+        delivery::{DeliveryPoint, DeliveryPointKind},
+        tile::{DecorationTile, SolidTile, TileClass},
+        MapData,
+    },
+    replay::Actions,
+};
+
+use super::{
+    cargo::{Cargo, Carrier, Shock},
+    depth::{self, YSort},
+    dialogue::in_dialogue,
+    economy::{Money, Transaction, TransactionKind},
+    input::StepInput,
+    interaction::{Interactable, Interacted},
+    map::{is_road, is_solid, tile_to_world, TILE_SIZE},
+    player::{walkable, Direction, Player, Velocity},
+    simulation::{SimulationEvents, SimulationSystems},
+    traffic::Obstacle,
+    GameSystemSet, OnGameScreen,
+};
+
+/// Distance from the vehicle at which courier is able to enter it.
+const ENTER_DISTANCE: f32 = TILE_SIZE * 1.5;
+/// Speed below which vehicle is considered to be standing still.
+const PARKING_SPEED: f32 = 5.;
+/// Part of the maximal speed that is available in reverse.
+const REVERSE_SHARE: f32 = 0.3;
+/// Deceleration of the vehicle when neither accelerating nor braking.
+const ROLLING_RESISTANCE: f32 = 30.;
+/// Shock applied to the cargo when vehicle hits a wall at walking speed.
+const COLLISION_SHOCK: f32 = 0.5;
+/// Speed that the courier walks at, used as a reference for shocks.
+const REFERENCE_SPEED: f32 = 50.;
+
+pub struct VehiclePlugin;
+
+impl Plugin for VehiclePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Garage>()
+            .add_simulation_event::<VehicleEvent>()
+            .add_step_systems((
+                spawn_vehicles,
+                drive.run_if(not(in_dialogue)),
+                update_prompts,
+                enter_or_exit,
+            ))
+            .add_system(animate_vehicles.in_set(GameSystemSet));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VehicleKind {
+    Bike,
+    CargoBike,
+    Van,
+}
+
+impl VehicleKind {
+    /// Maximal speed in world units per second.
+    pub fn max_speed(&self) -> f32 {
+        match self {
+            VehicleKind::Bike => 110.,
+            VehicleKind::CargoBike => 80.,
+            VehicleKind::Van => 160.,
+        }
+    }
+
+    /// Acceleration in world units per second squared.
+    pub fn acceleration(&self) -> f32 {
+        match self {
+            VehicleKind::Bike => 80.,
+            VehicleKind::CargoBike => 50.,
+            VehicleKind::Van => 60.,
+        }
+    }
+
+    /// Deceleration while braking in world units per second squared.
+    pub fn braking(&self) -> f32 {
+        match self {
+            VehicleKind::Bike => 200.,
+            VehicleKind::CargoBike => 150.,
+            VehicleKind::Van => 120.,
+        }
+    }
+
+    /// Radius of the tightest turn in world units.
+    pub fn turning_radius(&self) -> f32 {
+        match self {
+            VehicleKind::Bike => 8.,
+            VehicleKind::CargoBike => 12.,
+            VehicleKind::Van => 32.,
+        }
+    }
+
+    /// Maximal weight of the cargo in kilograms.
+    pub fn capacity(&self) -> f32 {
+        match self {
+            VehicleKind::Bike => 10.,
+            VehicleKind::CargoBike => 80.,
+            VehicleKind::Van => 600.,
+        }
+    }
+
+    /// Whether the vehicle is only able to drive over road tiles.
+    pub fn road_only(&self) -> bool {
+        matches!(self, VehicleKind::Van)
+    }
+
+    /// Price of the fuel burnt for every tile driven, if vehicle needs any.
+    pub fn fuel_price(&self) -> Option<Money> {
+        match self {
+            VehicleKind::Van => Some(Money::cents(2)),
+            _ => None,
+        }
+    }
+
+    /// Price of the repairs after hitting a wall at walking speed.
+    pub fn repair_price(&self) -> Money {
+        match self {
+            VehicleKind::Bike | VehicleKind::CargoBike => Money::cents(50),
+            VehicleKind::Van => Money::dollars(2),
+        }
+    }
+
+    /// Half size of the area that collides with solid tiles.
+    fn hitbox(&self) -> Vec2 {
+        match self {
+            VehicleKind::Bike | VehicleKind::CargoBike => Vec2::new(5., 4.),
+            VehicleKind::Van => Vec2::new(6., 6.),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VehicleKind::Bike => "bike",
+            VehicleKind::CargoBike => "cargo bike",
+            VehicleKind::Van => "van",
+        }
+    }
+}
+
+impl From<campaign::Vehicle> for VehicleKind {
+    fn from(vehicle: campaign::Vehicle) -> Self {
+        match vehicle {
+            campaign::Vehicle::Bike => VehicleKind::Bike,
+            campaign::Vehicle::CargoBike => VehicleKind::CargoBike,
+            campaign::Vehicle::Van => VehicleKind::Van,
+        }
+    }
+}
+
+/// Vehicles that are parked near the brewery when the map is loaded.
+#[derive(Resource, Debug, Clone)]
+pub struct Garage(pub Vec<VehicleKind>);
+
+impl Default for Garage {
+    fn default() -> Self {
+        Self(vec![
+            VehicleKind::Bike,
+            VehicleKind::CargoBike,
+            VehicleKind::Van,
+        ])
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Vehicle {
+    pub kind: VehicleKind,
+    /// Direction of the vehicle in radians, counterclockwise from the X axis.
+    heading: f32,
+    /// Signed speed along the heading, negative when reversing.
+    speed: f32,
+    /// Distance driven since the fuel was paid for last time.
+    odometer: f32,
+}
+
+impl Vehicle {
+    pub fn new(kind: VehicleKind, heading: f32) -> Self {
+        Self {
+            kind,
+            heading,
+            speed: 0.,
+            odometer: 0.,
+        }
+    }
+
+    pub fn is_parked(&self) -> bool {
+        self.speed.abs() < PARKING_SPEED
+    }
+
+    fn direction(&self) -> Vec2 {
+        Vec2::from_angle(self.heading)
+    }
+
+    fn facing(&self) -> Direction {
+        let direction = self.direction();
+        if direction.x.abs() > direction.y.abs() {
+            if direction.x > 0. {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if direction.y > 0. {
+            Direction::Up
+        } else {
+            Direction::Down
+        }
+    }
+}
+
+/// Vehicle that is driven by the courier.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Driving(pub Entity);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VehicleEvent {
+    Entered(VehicleKind),
+    Parked(VehicleKind),
+    /// Courier can't take the cargo in or out of the vehicle.
+    CargoDoesNotFit(VehicleKind),
+}
+
+/// Places vehicles of the [Garage] near the brewery.
+///
+/// Vehicles that only drive on roads are left out on maps without any road tiles.
+fn spawn_vehicles(
+    maps: Query<&MapData<DecorationTile>, Added<OnGameScreen>>,
+    solid: Query<&MapData<SolidTile>>,
+    points: Query<&DeliveryPoint>,
+    garage: Res<Garage>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let Ok(map) = maps.get_single() else {
+        return;
+    };
+    let Some(brewery) = points
+        .iter()
+        .find(|point| point.kind == DeliveryPointKind::Brewery)
+    else {
+        return;
+    };
+
+    // Free tiles, closest to the brewery door first.
+    let mut tiles: Vec<(usize, usize)> = map
+        .tiles()
+        .filter(|&((x, y), _)| {
+            (x, y) != (brewery.x, brewery.y) && solid.iter().all(|map| map.get(x, y).is_none())
+        })
+        .map(|(position, _)| position)
+        .collect();
+    let distance = |&(x, y): &(usize, usize)| x.abs_diff(brewery.x) + y.abs_diff(brewery.y);
+    tiles.sort_by_key(distance);
+
+    let atlases = VehicleAtlases::new(&asset_server, &mut texture_atlases);
+    let mut sidewalk = tiles
+        .iter()
+        .filter(|&&(x, y)| map.get(x, y).is_some_and(|tile| !tile.road()));
+    let mut road = tiles
+        .iter()
+        .filter(|&&(x, y)| map.get(x, y).is_some_and(TileClass::road));
+    for &kind in &garage.0 {
+        let tile = if kind.road_only() {
+            road.next()
+        } else {
+            sidewalk.next()
+        };
+        let Some(&(x, y)) = tile else {
+            continue;
+        };
+        let position = tile_to_world(map.size(), x, y);
+        let vehicle = Vehicle::new(kind, -FRAC_PI_2);
+        commands.spawn((
+            SpriteSheetBundle {
+                texture_atlas: atlases.get(kind),
+                sprite: TextureAtlasSprite::new(VehicleAtlases::index(kind, vehicle.facing())),
+                transform: Transform::from_translation(position.extend(depth::SPRITES)),
+                ..default()
+            },
+            vehicle,
+            Interactable::new(ENTER_DISTANCE),
+            YSort,
+            OnGameScreen,
+        ));
+    }
+}
+
+/// Spritesheets of the vehicles.
+///
+/// Van is taken from the city tileset, while bikes have their own spritesheet. Both of them
+/// contain sprites facing down, up, left and right in that order.
+///
+/// Van facing down or up is 20 pixels tall, so its sprites start 4 pixels above the grid of the
+/// tileset and end on it, right above the sprites facing to the sides.
+struct VehicleAtlases {
+    bikes: Handle<TextureAtlas>,
+    van: Handle<TextureAtlas>,
+}
+
+impl VehicleAtlases {
+    fn new(asset_server: &AssetServer, texture_atlases: &mut Assets<TextureAtlas>) -> Self {
+        let bikes = TextureAtlas::from_grid(
+            asset_server.load("bikes.png"),
+            Vec2::new(16., 16.),
+            4,
+            2,
+            None,
+            None,
+        );
+
+        let mut van = TextureAtlas::new_empty(
+            asset_server.load("city_tiles.png"),
+            Vec2::new(27. * 16., 18. * 16.),
+        );
+        for (min, max) in [
+            (Vec2::new(336., 236.), Vec2::new(352., 256.)),
+            (Vec2::new(352., 236.), Vec2::new(368., 256.)),
+            (Vec2::new(336., 256.), Vec2::new(368., 272.)),
+            (Vec2::new(336., 272.), Vec2::new(368., 288.)),
+        ] {
+            van.add_texture(Rect::from_corners(min, max));
+        }
+
+        Self {
+            bikes: texture_atlases.add(bikes),
+            van: texture_atlases.add(van),
+        }
+    }
+
+    fn get(&self, kind: VehicleKind) -> Handle<TextureAtlas> {
+        match kind {
+            VehicleKind::Bike | VehicleKind::CargoBike => self.bikes.clone(),
+            VehicleKind::Van => self.van.clone(),
+        }
+    }
+
+    fn index(kind: VehicleKind, facing: Direction) -> usize {
+        let row = match kind {
+            VehicleKind::Bike | VehicleKind::Van => 0,
+            VehicleKind::CargoBike => 4,
+        };
+        let column = match facing {
+            Direction::Down => 0,
+            Direction::Up => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+        };
+        row + column
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drive(
+    time: Res<Time>,
+    input: Res<StepInput>,
+    mut players: Query<(Entity, &mut Transform, &Driving, &Cargo, &Player)>,
+    mut vehicles: Query<(&mut Transform, &mut Vehicle), Without<Player>>,
+    decoration: Query<&MapData<DecorationTile>>,
+    solid: Query<&MapData<SolidTile>>,
+    obstacles: Query<(&Transform, &Obstacle), (Without<Player>, Without<Vehicle>)>,
+    mut shocks: EventWriter<Shock>,
+    mut transactions: EventWriter<Transaction>,
+) {
+    for (courier, mut player, driving, cargo, &Player { index, .. }) in &mut players {
+        let Ok((mut transform, mut vehicle)) = vehicles.get_mut(driving.0) else {
+            continue;
+        };
+        let kind = vehicle.kind;
+        let dt = time.delta_seconds();
+
+        // Heavy cargo makes vehicle both slower and more sluggish.
+        let max_speed = kind.max_speed() * cargo.speed();
+        let acceleration = kind.acceleration() * cargo.speed();
+        let actions = input.pressed(index);
+        let throttle = actions.contains(Actions::UP);
+        let brake = actions.contains(Actions::DOWN);
+        vehicle.speed = if throttle {
+            (vehicle.speed + acceleration * dt).min(max_speed)
+        } else if brake && vehicle.speed > 0. {
+            (vehicle.speed - kind.braking() * dt).max(0.)
+        } else if brake {
+            (vehicle.speed - acceleration * dt).max(-max_speed * REVERSE_SHARE)
+        } else {
+            vehicle.speed.signum() * (vehicle.speed.abs() - ROLLING_RESISTANCE * dt).max(0.)
+        };
+
+        // Vehicle turns along a circle, so it is unable to turn while standing still.
+        let mut steering = 0.;
+        if actions.contains(Actions::LEFT) {
+            steering += 1.;
+        }
+        if actions.contains(Actions::RIGHT) {
+            steering -= 1.;
+        }
+        vehicle.heading =
+            (vehicle.heading + steering * vehicle.speed / kind.turning_radius() * dt) % (2. * PI);
+
+        let position = transform.translation.truncate();
+        let target = position + vehicle.direction() * vehicle.speed * dt;
+        let corners = {
+            let hitbox = kind.hitbox();
+            [
+                Vec2::new(-hitbox.x, -hitbox.y),
+                Vec2::new(hitbox.x, -hitbox.y),
+                Vec2::new(-hitbox.x, hitbox.y),
+                Vec2::new(hitbox.x, hitbox.y),
+            ]
+            .map(|corner| target + corner)
+        };
+        let hits_wall = solid
+            .iter()
+            .any(|map| corners.iter().any(|&corner| is_solid(map, corner)))
+            || obstacles.iter().any(|(obstacle, Obstacle { radius })| {
+                let distance = obstacle.translation.truncate().distance(target);
+                // Obstacles that vehicle already touches don't stop it from driving away.
+                distance < radius + kind.hitbox().x
+                    && distance < obstacle.translation.truncate().distance(position)
+            });
+        let leaves_road = kind.road_only()
+            && decoration
+                .iter()
+                .any(|map| corners.iter().any(|&corner| !is_road(map, corner)));
+
+        if hits_wall {
+            // Slowly pushing against the wall doesn't damage anything.
+            if !vehicle.is_parked() {
+                let impact = vehicle.speed.abs() / REFERENCE_SPEED;
+                shocks.send(Shock {
+                    courier,
+                    amount: COLLISION_SHOCK * impact,
+                });
+                transactions.send(Transaction::expense(
+                    TransactionKind::Repair,
+                    kind.repair_price() * impact,
+                ));
+            }
+            vehicle.speed = 0.;
+        } else if leaves_road {
+            vehicle.speed = 0.;
+        } else {
+            vehicle.odometer += position.distance(target);
+            transform.translation.x = target.x;
+            transform.translation.y = target.y;
+        }
+
+        player.translation.x = transform.translation.x;
+        player.translation.y = transform.translation.y;
+    }
+}
+
+/// Offers to get on vehicles that stand still, or off the ones that are driven.
+///
+/// Getting off is only offered to the driver.
+fn update_prompts(
+    driving: Query<(Entity, &Driving), With<Player>>,
+    mut vehicles: Query<(Entity, &Vehicle, &mut Interactable)>,
+) {
+    for (entity, vehicle, mut interactable) in &mut vehicles {
+        let driver = driving
+            .iter()
+            .find(|(_, driving)| driving.0 == entity)
+            .map(|(driver, _)| driver);
+        let prompt = if !vehicle.is_parked() {
+            None
+        } else if driver.is_some() {
+            Some(format!("Get off the {}", vehicle.kind.name()))
+        } else {
+            Some(format!("Get on the {}", vehicle.kind.name()))
+        };
+        if interactable.prompt != prompt || interactable.courier != driver {
+            interactable.prompt = prompt;
+            interactable.courier = driver;
+        }
+    }
+}
+
+fn enter_or_exit(
+    mut interactions: EventReader<Interacted>,
+    mut players: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Visibility,
+            &mut Velocity,
+            &mut Cargo,
+            Option<&Driving>,
+        ),
+        With<Player>,
+    >,
+    mut vehicles: Query<(Entity, &Transform, &mut Vehicle), Without<Player>>,
+    solid: Query<&MapData<SolidTile>>,
+    mut commands: Commands,
+    mut events: EventWriter<VehicleEvent>,
+    mut transactions: EventWriter<Transaction>,
+) {
+    let mut driven: Vec<_> = players
+        .iter()
+        .filter_map(|(.., driving)| driving.map(|driving| driving.0))
+        .collect();
+    for &Interacted { target, courier } in interactions.iter() {
+        let Ok((entity, mut transform, mut visibility, mut velocity, mut cargo, driving)) =
+            players.get_mut(courier)
+        else {
+            continue;
+        };
+
+        if let Some(driving) = driving {
+            if driving.0 != target {
+                continue;
+            }
+            let Ok((_, vehicle_transform, mut vehicle)) = vehicles.get_mut(driving.0) else {
+                continue;
+            };
+            if !vehicle.is_parked() {
+                continue;
+            }
+            let kind = vehicle.kind;
+            let Some(carrier) = Carrier::for_weight(cargo.weight()) else {
+                events.send(VehicleEvent::CargoDoesNotFit(kind));
+                continue;
+            };
+
+            // Courier gets out at the side of the vehicle, or anywhere around it if side is
+            // blocked.
+            let center = vehicle_transform.translation.truncate();
+            let side = vehicle.direction().perp() * TILE_SIZE;
+            let exit = [side, -side, side.perp(), -side.perp()]
+                .into_iter()
+                .map(|offset| center + offset)
+                .find(|&exit| solid.iter().all(|map| walkable(map, exit)));
+            let Some(exit) = exit else {
+                continue;
+            };
+
+            if let Some(price) = kind.fuel_price() {
+                let tiles = vehicle.odometer / TILE_SIZE;
+                transactions.send(Transaction::expense(TransactionKind::Fuel, price * tiles));
+            }
+            vehicle.speed = 0.;
+            vehicle.odometer = 0.;
+            let _ = cargo.set_carrier(carrier);
+            transform.translation.x = exit.x;
+            transform.translation.y = exit.y;
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<Driving>();
+            driven.retain(|&driven| driven != driving.0);
+            events.send(VehicleEvent::Parked(kind));
+        } else {
+            let Ok((vehicle, _, target)) = vehicles.get(target) else {
+                continue;
+            };
+            if driven.contains(&vehicle) {
+                continue;
+            }
+            let kind = target.kind;
+            if cargo.set_carrier(Carrier::Vehicle(kind)).is_err() {
+                events.send(VehicleEvent::CargoDoesNotFit(kind));
+                continue;
+            }
+            *velocity = Velocity::default();
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).insert(Driving(vehicle));
+            driven.push(vehicle);
+            events.send(VehicleEvent::Entered(kind));
+        }
+    }
+}
+
+fn animate_vehicles(mut vehicles: Query<(&Vehicle, &mut TextureAtlasSprite), Changed<Vehicle>>) {
+    for (vehicle, mut sprite) in &mut vehicles {
+        let index = VehicleAtlases::index(vehicle.kind, vehicle.facing());
+        if sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! Game and the data formats shared with its tooling.
+
+#![allow(clippy::type_complexity)]
+
+use bevy::prelude::*;
+
+pub mod audio;
+pub mod campaign;
+pub mod dialogue;
+pub mod game;
+pub mod map;
+pub mod menu;
+pub mod replay;
+pub mod reputation;
+pub mod save;
+pub mod score;
+
+#[derive(States, PartialEq, Eq, Debug, Clone, Hash, Default)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    Game,
+}
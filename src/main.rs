@@ -1,20 +1,6 @@
-#![allow(clippy::type_complexity)]
-
+use beer_delivery::{audio::SoundPlugin, game::GamePlugin, menu::MenuPlugin, GameState};
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
-use game::GamePlugin;
-use menu::MenuPlugin;
-
-mod game;
-mod map;
-mod menu;
-
-#[derive(States, PartialEq, Eq, Debug, Clone, Hash, Default)]
-enum GameState {
-    #[default]
-    MainMenu,
-    Game,
-}
 
 fn main() {
     const TITLE: &str = "Beer Delivery";
@@ -36,6 +22,7 @@ fn main() {
         .add_startup_system(setup)
         .add_plugin(MenuPlugin)
         .add_plugin(GamePlugin)
+        .add_plugin(SoundPlugin)
         .run();
 }
 
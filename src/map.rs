@@ -1,7 +1,16 @@
 //! [MapData] is a data object used to save and load tilemaps to and from the disk.
-
+//!
+//! Maps are stored in compact binary format by default. Files with `.ron` extension are stored
+//! in human-readable text format instead, see [MapFormat].
+
+pub mod bounds;
+pub mod delivery;
+pub mod edit;
+pub mod generator;
+pub mod navigation;
 pub mod preset;
 pub mod tile;
+pub mod validation;
 
 use std::{ops::IndexMut, path::Path, slice::ChunksExact};
 
@@ -11,8 +20,9 @@ use rmp_serde::{decode, encode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use tile::TileClass;
+use validation::{Diagnostic, Problem, ValidationRules};
 
-#[derive(Debug, Serialize, Deserialize, Component)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
 pub struct MapData<Tile: TileClass> {
     spritesheet: String,
     tiles: Vec<Option<Tile>>,
@@ -32,14 +42,105 @@ impl<T: TileClass> MapData<T> {
         MapIterator::new(self)
     }
 
+    /// Returns mutable reference to the tile at provided position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if position is out of bounds, see [MapData::set] for checked alternative.
     pub fn get_mut(&mut self, x: usize, y: usize) -> &mut Option<T> {
         self.tiles.index_mut(x + y * self.width)
     }
 
+    pub fn spritesheet(&self) -> &str {
+        &self.spritesheet
+    }
+
     pub fn size(&self) -> TilemapSize {
         TilemapSize {
             x: self.width as u32,
-            y: (self.tiles.len() / self.width) as u32,
+            y: self.tiles.len().checked_div(self.width).unwrap_or(0) as u32,
+        }
+    }
+
+    /// Checks the map for consistency.
+    ///
+    /// Returned diagnostics are ordered by position of the tile that caused them.
+    pub fn validate(&self, rules: &ValidationRules) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut report = |problem, position| diagnostics.push(Diagnostic { problem, position });
+
+        if self.width == 0 {
+            report(Problem::ZeroWidth, None);
+        } else if self.tiles.is_empty() {
+            report(Problem::Empty, None);
+        } else if !self.tiles.len().is_multiple_of(self.width) {
+            report(
+                Problem::IncompleteRow {
+                    tiles: self.tiles.len(),
+                    width: self.width,
+                },
+                None,
+            );
+        }
+
+        if let Some(expected) = rules.size() {
+            let found = self.size();
+            if (expected.x, expected.y) != (found.x, found.y) {
+                report(
+                    Problem::SizeMismatch {
+                        expected: (expected.x, expected.y),
+                        found: (found.x, found.y),
+                    },
+                    None,
+                );
+            }
+        }
+
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let Some(tile) = tile else {
+                continue;
+            };
+            let position = Some((i % self.width.max(1), i / self.width.max(1)));
+            if let Some(texture_count) = rules.texture_count() {
+                if tile.texture() >= texture_count {
+                    report(
+                        Problem::TextureOutOfRange {
+                            texture: tile.texture(),
+                            texture_count,
+                        },
+                        position,
+                    );
+                }
+            }
+            if let Some(destination) = tile.destination() {
+                if !rules.destination_exists(destination) {
+                    report(
+                        Problem::MissingDestination(destination.to_owned()),
+                        position,
+                    );
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Format of the map file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapFormat {
+    /// MessagePack encoded map.
+    Binary,
+    /// RON encoded map that may be edited by hand.
+    Text,
+}
+
+impl MapFormat {
+    /// Detects format of the file by its extension.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => MapFormat::Text,
+            _ => MapFormat::Binary,
         }
     }
 }
@@ -50,6 +151,10 @@ pub enum MapLoadError {
     FileSystemError(#[from] std::io::Error),
     #[error("provided file is not in valid format: {0}")]
     DecodeError(#[from] rmp_serde::decode::Error),
+    #[error("provided file is not in valid text format: {0}")]
+    TextDecodeError(#[from] ron::error::SpannedError),
+    #[error("provided map is invalid: {}", .0[0])]
+    InvalidMap(Vec<Diagnostic>),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -58,6 +163,8 @@ pub enum MapSaveError {
     FileSystemError(#[from] std::io::Error),
     #[error("provided map could not be encoded: {0}")]
     EncodeError(#[from] rmp_serde::encode::Error),
+    #[error("provided map could not be encoded as text: {0}")]
+    TextEncodeError(#[from] ron::Error),
 }
 
 impl<T> MapData<T>
@@ -65,8 +172,26 @@ where
     T: TileClass + DeserializeOwned,
 {
     pub fn load(path: &Path) -> Result<Self, MapLoadError> {
+        Self::load_as(path, MapFormat::from_path(path))
+    }
+
+    /// Loads map in provided format.
+    ///
+    /// Maps that fail [validation](MapData::validate) with default rules are rejected.
+    pub fn load_as(path: &Path, format: MapFormat) -> Result<Self, MapLoadError> {
         let bytes = std::fs::read(path)?;
-        let map = decode::from_slice::<MapData<T>>(&bytes)?;
+        let map = match format {
+            MapFormat::Binary => decode::from_slice::<MapData<T>>(&bytes)?,
+            MapFormat::Text => ron::de::from_bytes::<MapData<T>>(&bytes)?,
+        };
+        let errors: Vec<_> = map
+            .validate(&ValidationRules::default())
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect();
+        if !errors.is_empty() {
+            return Err(MapLoadError::InvalidMap(errors));
+        }
         Ok(map)
     }
 }
@@ -76,7 +201,16 @@ where
     T: TileClass + Serialize,
 {
     pub fn save(&self, path: &Path) -> Result<(), MapSaveError> {
-        let bytes = encode::to_vec(self)?;
+        self.save_as(path, MapFormat::from_path(path))
+    }
+
+    pub fn save_as(&self, path: &Path, format: MapFormat) -> Result<(), MapSaveError> {
+        let bytes = match format {
+            MapFormat::Binary => encode::to_vec(self)?,
+            MapFormat::Text => {
+                ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?.into_bytes()
+            }
+        };
         std::fs::write(path, bytes)?;
         Ok(())
     }
@@ -91,7 +225,8 @@ where
     Tile: TileClass,
 {
     pub fn new(map: &'map MapData<Tile>) -> Self {
-        let row_size = map.width;
+        // Zero width maps are invalid, but that shouldn't be a reason to panic.
+        let row_size = map.width.max(1);
         Self {
             chunks: map.tiles.chunks_exact(row_size),
         }
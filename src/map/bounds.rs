@@ -0,0 +1,116 @@
+/// Inclusive rectangular area of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min_x: usize,
+    pub min_y: usize,
+    pub max_x: usize,
+    pub max_y: usize,
+}
+
+impl Bounds {
+    /// Amount of columns in the area, which is zero when `min_x` is past `max_x`.
+    pub fn width(&self) -> usize {
+        if self.max_x < self.min_x {
+            0
+        } else {
+            (self.max_x - self.min_x).saturating_add(1)
+        }
+    }
+
+    /// Amount of rows in the area, which is zero when `min_y` is past `max_y`.
+    pub fn height(&self) -> usize {
+        if self.max_y < self.min_y {
+            0
+        } else {
+            (self.max_y - self.min_y).saturating_add(1)
+        }
+    }
+
+    /// Returns location of the tile relative to the edges of the area.
+    pub fn location(&self, x: usize, y: usize) -> Location {
+        if x == self.min_x && y == self.min_y {
+            return Location::BottomLeft;
+        }
+        if x == self.min_x && y == self.max_y {
+            return Location::TopLeft;
+        }
+        if x == self.max_x && y == self.min_y {
+            return Location::BottomRight;
+        }
+        if x == self.max_x && y == self.max_y {
+            return Location::TopRight;
+        }
+        if x == self.min_x {
+            return Location::Left;
+        }
+        if x == self.max_x {
+            return Location::Right;
+        }
+        if y == self.min_y {
+            return Location::Bottom;
+        }
+        if y == self.max_y {
+            return Location::Top;
+        }
+        Location::Center
+    }
+
+    /// Returns the same area with every edge moved inwards by `by` tiles.
+    pub fn shrink(&self, by: usize) -> Bounds {
+        Bounds {
+            min_x: self.min_x + by,
+            min_y: self.min_y + by,
+            max_x: self.max_x - by,
+            max_y: self.max_y - by,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> {
+        let Bounds {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        } = *self;
+        (min_x..=max_x).flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Texture indices of a tileset area that is stretched over [Bounds] by repeating its middle.
+#[derive(Debug, Clone, Copy)]
+pub struct NinePatch {
+    /// Texture index of the top left corner.
+    pub origin: u32,
+    /// Amount of tiles in one row of the tileset.
+    pub stride: u32,
+}
+
+impl NinePatch {
+    pub fn texture(&self, location: Location) -> u32 {
+        let (column, row) = match location {
+            Location::TopLeft => (0, 0),
+            Location::Top => (1, 0),
+            Location::TopRight => (2, 0),
+            Location::Left => (0, 1),
+            Location::Center => (1, 1),
+            Location::Right => (2, 1),
+            Location::BottomLeft => (0, 2),
+            Location::Bottom => (1, 2),
+            Location::BottomRight => (2, 2),
+        };
+        self.origin + row * self.stride + column
+    }
+}
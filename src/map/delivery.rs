@@ -0,0 +1,20 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// A place on the map where beer is picked up or delivered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Component)]
+pub struct DeliveryPoint {
+    pub kind: DeliveryPointKind,
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeliveryPointKind {
+    /// Place where beer is brewed and picked up.
+    Brewery,
+    /// Bar that orders beer in bulk.
+    Bar,
+    /// Private customer.
+    Customer,
+}
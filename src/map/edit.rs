@@ -0,0 +1,260 @@
+//! Editing operations of [MapData].
+//!
+//! Coordinates used here are the same as in the tilemap: `(0, 0)` is the bottom left tile,
+//! `x` grows to the right and `y` grows to the top.
+
+use super::{
+    bounds::{Bounds, Location},
+    tile::TileClass,
+    MapData,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("tile ({x}, {y}) is out of map bounds")]
+pub struct OutOfBounds {
+    pub x: usize,
+    pub y: usize,
+}
+
+/// Tile that differs between two layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileDiff<'map, T> {
+    pub x: usize,
+    pub y: usize,
+    pub before: Option<&'map T>,
+    pub after: Option<&'map T>,
+}
+
+impl<T: TileClass> MapData<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.size().y as usize
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height()
+    }
+
+    /// Returns tile at provided position, or `None` if it is empty or out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        self.tiles[x + y * self.width].as_ref()
+    }
+
+    /// Replaces tile at provided position and returns the previous one.
+    pub fn set(&mut self, x: usize, y: usize, tile: Option<T>) -> Result<Option<T>, OutOfBounds> {
+        if !self.contains(x, y) {
+            return Err(OutOfBounds { x, y });
+        }
+        Ok(std::mem::replace(&mut self.tiles[x + y * self.width], tile))
+    }
+
+    /// Iterates over all tiles of the map together with their positions.
+    pub fn tiles(&self) -> impl Iterator<Item = ((usize, usize), Option<&T>)> {
+        let width = self.width.max(1);
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(move |(i, tile)| ((i % width, i / width), tile.as_ref()))
+    }
+
+    /// Area that covers the whole map.
+    ///
+    /// Returns `None` if the map has no tiles.
+    pub fn bounds(&self) -> Option<Bounds> {
+        if self.width == 0 || self.height() == 0 {
+            return None;
+        }
+        Some(Bounds {
+            min_x: 0,
+            min_y: 0,
+            max_x: self.width - 1,
+            max_y: self.height() - 1,
+        })
+    }
+
+    /// Fills the area with provided tile. Parts of the area that are out of bounds are ignored.
+    pub fn fill(&mut self, area: &Bounds, tile: Option<T>) {
+        for (x, y) in area.iter() {
+            if self.contains(x, y) {
+                self.tiles[x + y * self.width] = tile.clone();
+            }
+        }
+    }
+
+    /// Creates a stamp from the area of the map. Parts of the area that are out of bounds are
+    /// left empty, and an area with its minimum past its maximum makes an empty stamp.
+    pub fn copy(&self, area: &Bounds) -> MapData<T> {
+        let mut stamp = MapData::new(area.width(), area.height(), self.spritesheet.clone());
+        for (x, y) in area.iter() {
+            stamp.tiles[(x - area.min_x) + (y - area.min_y) * stamp.width] =
+                self.get(x, y).cloned();
+        }
+        stamp
+    }
+
+    /// Places the stamp with its bottom left corner at provided position.
+    ///
+    /// Empty tiles of the stamp don't overwrite tiles of the map, and parts of the stamp that
+    /// are out of bounds are cut off.
+    pub fn paste(&mut self, stamp: &MapData<T>, x: usize, y: usize) {
+        for ((dx, dy), tile) in stamp.tiles() {
+            let (Some(x), Some(y)) = (x.checked_add(dx), y.checked_add(dy)) else {
+                continue;
+            };
+            if let (Some(tile), true) = (tile, self.contains(x, y)) {
+                self.tiles[x + y * self.width] = Some(tile.clone());
+            }
+        }
+    }
+
+    /// Changes size of the map, keeping the `anchor` side or corner of the map in place.
+    pub fn resize(&mut self, width: usize, height: usize, anchor: Location) {
+        let offset = |old: usize, new: usize, start: bool, end: bool| -> isize {
+            let difference = new as isize - old as isize;
+            match (start, end) {
+                (true, _) => 0,
+                (_, true) => difference,
+                _ => difference / 2,
+            }
+        };
+        let dx = offset(
+            self.width,
+            width,
+            matches!(
+                anchor,
+                Location::Left | Location::TopLeft | Location::BottomLeft
+            ),
+            matches!(
+                anchor,
+                Location::Right | Location::TopRight | Location::BottomRight
+            ),
+        );
+        let dy = offset(
+            self.height(),
+            height,
+            matches!(
+                anchor,
+                Location::Bottom | Location::BottomLeft | Location::BottomRight
+            ),
+            matches!(
+                anchor,
+                Location::Top | Location::TopLeft | Location::TopRight
+            ),
+        );
+
+        let mut resized = MapData::new(width, height, self.spritesheet.clone());
+        for ((x, y), tile) in self.tiles() {
+            let (x, y) = (x as isize + dx, y as isize + dy);
+            if x < 0 || y < 0 {
+                continue;
+            }
+            if let (Some(tile), true) = (tile, resized.contains(x as usize, y as usize)) {
+                resized.tiles[x as usize + y as usize * width] = Some(tile.clone());
+            }
+        }
+        *self = resized;
+    }
+
+    /// Mirrors the map from left to right.
+    pub fn flip_horizontal(&mut self) {
+        let width = self.width.max(1);
+        for row in self.tiles.chunks_exact_mut(width) {
+            row.reverse();
+        }
+    }
+
+    /// Mirrors the map from top to bottom.
+    ///
+    /// Tiles that don't make a complete row stay at the end, like they do when flipping the map
+    /// horizontally.
+    pub fn flip_vertical(&mut self) {
+        if self.width == 0 {
+            return;
+        }
+        let rows = self.tiles.chunks_exact(self.width);
+        let incomplete = rows.remainder();
+        let flipped: Vec<_> = rows.rev().flatten().chain(incomplete).cloned().collect();
+        self.tiles = flipped;
+    }
+
+    /// Rotates the map by 90 degrees clockwise.
+    ///
+    /// Only positions of tiles are changed, their textures stay the same. Tiles that don't make
+    /// a complete row are dropped.
+    pub fn rotate_clockwise(&mut self) {
+        let (width, height) = (self.width, self.height());
+        let mut rotated = MapData::new(height, width, self.spritesheet.clone());
+        for ((x, y), tile) in self.tiles() {
+            if self.contains(x, y) {
+                rotated.tiles[y + (width - 1 - x) * height] = tile.cloned();
+            }
+        }
+        *self = rotated;
+    }
+}
+
+impl<T: TileClass + PartialEq> MapData<T> {
+    /// Replaces the area of identical tiles connected to provided position with the new tile.
+    ///
+    /// Returns amount of replaced tiles.
+    pub fn flood_fill(&mut self, x: usize, y: usize, tile: Option<T>) -> usize {
+        if !self.contains(x, y) {
+            return 0;
+        }
+        let target = self.get(x, y).cloned();
+        if target == tile {
+            return 0;
+        }
+
+        let mut filled = 0;
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            // Filled tiles no longer match the target, so they are never visited twice.
+            if !self.contains(x, y) || self.get(x, y) != target.as_ref() {
+                continue;
+            }
+            self.tiles[x + y * self.width] = tile.clone();
+            filled += 1;
+
+            stack.push((x + 1, y));
+            stack.push((x, y + 1));
+            if let Some(x) = x.checked_sub(1) {
+                stack.push((x, y));
+            }
+            if let Some(y) = y.checked_sub(1) {
+                stack.push((x, y));
+            }
+        }
+        filled
+    }
+
+    /// Lists tiles that differ between this layer and the other one.
+    ///
+    /// Tiles that are out of bounds of one of the layers are treated as empty.
+    pub fn diff<'map>(&'map self, other: &'map MapData<T>) -> Vec<TileDiff<'map, T>> {
+        let width = self.width.max(other.width);
+        let height = self.height().max(other.height());
+
+        let mut diff = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let (before, after) = (self.get(x, y), other.get(x, y));
+                if before != after {
+                    diff.push(TileDiff {
+                        x,
+                        y,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        diff
+    }
+}
@@ -0,0 +1,249 @@
+//! Seeded procedural generator of city maps.
+//!
+//! City is a grid of blocks separated by roads. Every block is surrounded by sidewalk and
+//! contains either a park or a building, some of which are used as [DeliveryPoint]s.
+//! The same seed always produces the same city.
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    bounds::{Bounds, Location, NinePatch},
+    delivery::{DeliveryPoint, DeliveryPointKind},
+    tile::{DecorationTile, SolidTile},
+    MapData,
+};
+
+const SPRITESHEET: &str = "city_tiles.png";
+const STRIDE: u32 = 27;
+
+const ROAD_WIDTH: usize = 3;
+const MIN_BLOCK_SIZE: usize = 6;
+const MAX_BLOCK_SIZE: usize = 10;
+const PARK_CHANCE: f64 = 0.2;
+const BAR_CHANCE: f64 = 0.3;
+
+const ASPHALT: u32 = 15 * STRIDE + 1;
+const HORIZONTAL_MARKING: u32 = 16 * STRIDE + 1;
+const VERTICAL_MARKING: u32 = 17 * STRIDE + 3;
+const CROSSING_MARKING: u32 = 15 * STRIDE + 2;
+
+const SIDEWALK: NinePatch = NinePatch {
+    origin: 8,
+    stride: STRIDE,
+};
+const GRASS: NinePatch = NinePatch {
+    origin: 0,
+    stride: STRIDE,
+};
+const ROOFS: [NinePatch; 2] = [
+    NinePatch {
+        origin: 3 * STRIDE + 8,
+        stride: STRIDE,
+    },
+    NinePatch {
+        origin: 3 * STRIDE,
+        stride: STRIDE,
+    },
+];
+const WALL_TOP: [u32; 3] = [17, 18, 19];
+const WALL_BOTTOM: [u32; 3] = [17 + 3 * STRIDE, 18 + 3 * STRIDE, 19 + 3 * STRIDE];
+
+/// Layers and delivery points of the generated city.
+pub struct City {
+    pub decoration: MapData<DecorationTile>,
+    pub solid: MapData<SolidTile>,
+    pub delivery_points: Vec<DeliveryPoint>,
+}
+
+pub struct CityGenerator {
+    seed: u64,
+    blocks_x: usize,
+    blocks_y: usize,
+}
+
+impl CityGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            blocks_x: 4,
+            blocks_y: 4,
+        }
+    }
+
+    /// Sets amount of blocks in every row and column of the city.
+    pub fn with_blocks(mut self, blocks_x: usize, blocks_y: usize) -> Self {
+        self.blocks_x = blocks_x.max(1);
+        self.blocks_y = blocks_y.max(1);
+        self
+    }
+
+    pub fn generate(&self) -> City {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+
+        let (columns, width) = Self::layout(&mut rng, self.blocks_x);
+        let (rows, height) = Self::layout(&mut rng, self.blocks_y);
+
+        let mut decoration = MapData::new(width, height, String::from(SPRITESHEET));
+        let mut solid = MapData::new(width, height, String::from(SPRITESHEET));
+
+        Self::generate_roads(&mut decoration, &columns, &rows);
+
+        let blocks: Vec<Bounds> = rows
+            .iter()
+            .flat_map(|row| {
+                columns.iter().map(|column| Bounds {
+                    min_x: column.start,
+                    min_y: row.start,
+                    max_x: column.end - 1,
+                    max_y: row.end - 1,
+                })
+            })
+            .collect();
+
+        let mut parks: Vec<bool> = blocks.iter().map(|_| rng.gen_bool(PARK_CHANCE)).collect();
+        if parks.iter().all(|&park| park) {
+            parks[0] = false;
+        }
+
+        let mut doors = Vec::new();
+        for (block, park) in blocks.iter().zip(parks) {
+            Self::fill(&mut decoration, block, SIDEWALK);
+            let inner = block.shrink(1);
+            if park {
+                Self::fill(&mut decoration, &inner, GRASS);
+            } else {
+                doors.push(Self::generate_building(&mut rng, &mut solid, &inner));
+            }
+        }
+
+        doors.shuffle(&mut rng);
+        let delivery_points = doors
+            .into_iter()
+            .enumerate()
+            .map(|(i, (x, y))| {
+                let kind = match i {
+                    0 => DeliveryPointKind::Brewery,
+                    1 => DeliveryPointKind::Bar,
+                    _ if rng.gen_bool(BAR_CHANCE) => DeliveryPointKind::Bar,
+                    _ => DeliveryPointKind::Customer,
+                };
+                DeliveryPoint { kind, x, y }
+            })
+            .collect();
+
+        City {
+            decoration,
+            solid,
+            delivery_points,
+        }
+    }
+
+    /// Splits one axis of the map into blocks separated by roads.
+    ///
+    /// Returns ranges occupied by blocks and total length of the axis.
+    fn layout(rng: &mut ChaCha8Rng, blocks: usize) -> (Vec<std::ops::Range<usize>>, usize) {
+        let mut ranges = Vec::with_capacity(blocks);
+        let mut start = ROAD_WIDTH;
+        for _ in 0..blocks {
+            let size = rng.gen_range(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE);
+            ranges.push(start..start + size);
+            start += size + ROAD_WIDTH;
+        }
+        (ranges, start)
+    }
+
+    fn generate_roads(
+        map: &mut MapData<DecorationTile>,
+        columns: &[std::ops::Range<usize>],
+        rows: &[std::ops::Range<usize>],
+    ) {
+        // Offset of the tile from the edge of the road it belongs to, if any.
+        let road_offset = |blocks: &[std::ops::Range<usize>], i: usize| {
+            if blocks.iter().any(|block| block.contains(&i)) {
+                return None;
+            }
+            let previous_end = blocks
+                .iter()
+                .map(|block| block.end)
+                .filter(|&end| end <= i)
+                .max()
+                .unwrap_or(0);
+            Some(i - previous_end)
+        };
+
+        let size = map.size();
+        for x in 0..size.x as usize {
+            for y in 0..size.y as usize {
+                let center = ROAD_WIDTH / 2;
+                let texture = match (road_offset(columns, x), road_offset(rows, y)) {
+                    (None, None) => continue,
+                    (Some(dx), Some(dy)) if dx == center && dy == center => CROSSING_MARKING,
+                    (Some(_), Some(_)) => ASPHALT,
+                    (Some(dx), None) if dx == center => VERTICAL_MARKING,
+                    (None, Some(dy)) if dy == center => HORIZONTAL_MARKING,
+                    _ => ASPHALT,
+                };
+                *map.get_mut(x, y) = Some(DecorationTile { texture });
+            }
+        }
+    }
+
+    /// Places building over provided area and returns position of its door.
+    fn generate_building(
+        rng: &mut ChaCha8Rng,
+        map: &mut MapData<SolidTile>,
+        area: &Bounds,
+    ) -> (usize, usize) {
+        let roof = Bounds {
+            min_y: area.min_y + 2,
+            ..*area
+        };
+        Self::fill(map, &roof, *ROOFS.choose(rng).unwrap());
+
+        for x in roof.min_x..=roof.max_x {
+            if let Some(tile) = map.get_mut(x, roof.max_y) {
+                tile.overhang = true;
+            }
+        }
+
+        for x in area.min_x..=area.max_x {
+            let column = match roof.location(x, roof.min_y) {
+                Location::BottomLeft => 0,
+                Location::BottomRight => 2,
+                _ => 1,
+            };
+            *map.get_mut(x, area.min_y + 1) = Some(SolidTile::with_texture(WALL_TOP[column]));
+            *map.get_mut(x, area.min_y) = Some(SolidTile::with_texture(WALL_BOTTOM[column]));
+        }
+
+        // Door leads to the sidewalk right in front of the wall.
+        (area.min_x + area.width() / 2, area.min_y - 1)
+    }
+
+    fn fill<T: TexturedTile>(map: &mut MapData<T>, area: &Bounds, patch: NinePatch) {
+        for (x, y) in area.iter() {
+            *map.get_mut(x, y) = Some(T::with_texture(patch.texture(area.location(x, y))));
+        }
+    }
+}
+
+/// Tile that generator is able to construct from texture index.
+trait TexturedTile: super::tile::TileClass {
+    fn with_texture(texture: u32) -> Self;
+}
+
+impl TexturedTile for DecorationTile {
+    fn with_texture(texture: u32) -> Self {
+        DecorationTile { texture }
+    }
+}
+
+impl TexturedTile for SolidTile {
+    fn with_texture(texture: u32) -> Self {
+        SolidTile {
+            texture,
+            overhang: false,
+        }
+    }
+}
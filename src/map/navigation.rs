@@ -0,0 +1,345 @@
+//! Paths between tiles of the map.
+//!
+//! [NavGrid] is built from decoration and solid layers and knows how much it costs to enter
+//! every tile when walking or driving. Paths are found over four neighbouring tiles either
+//! with A*, which respects tile costs, or with jump point search, which is faster on large
+//! open areas but treats all passable tiles as equal, see [Algorithm].
+//!
+//! Grid is kept up to date with [NavGrid::update_solid] and [NavGrid::update_decoration],
+//! which take the changes produced by [MapData::diff].
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::prelude::Resource;
+
+use super::{
+    edit::{OutOfBounds, TileDiff},
+    tile::{DecorationTile, SolidTile, TileClass},
+    MapData,
+};
+
+/// Cost of entering an ordinary tile.
+pub const BASE_COST: u32 = 10;
+/// Cost of walking over the road, so that pedestrians only cross roads when they need to.
+const ROAD_CROSSING_COST: u32 = 40;
+
+/// Way of moving over the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Movement {
+    /// Everything that isn't a building, but roads are avoided.
+    Walking,
+    /// Roads only.
+    Driving,
+}
+
+impl Movement {
+    fn cost(&self, tile: &NavTile) -> Option<u32> {
+        if tile.solid {
+            return None;
+        }
+        let cost = match (self, tile.road) {
+            (Movement::Walking, false) | (Movement::Driving, true) => BASE_COST,
+            (Movement::Walking, true) => ROAD_CROSSING_COST,
+            (Movement::Driving, false) => return None,
+        };
+        Some(cost + tile.penalty)
+    }
+}
+
+/// Pathfinding algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Algorithm {
+    /// Finds the cheapest path.
+    #[default]
+    AStar,
+    /// Finds the shortest path, ignoring the costs of the tiles.
+    JumpPoint,
+}
+
+/// Tile properties that matter for navigation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct NavTile {
+    solid: bool,
+    road: bool,
+    /// Extra cost of entering the tile, for both walking and driving.
+    penalty: u32,
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct NavGrid {
+    tiles: Vec<NavTile>,
+    width: usize,
+    height: usize,
+}
+
+impl NavGrid {
+    /// Builds navigation grid from map layers.
+    ///
+    /// Overhanging tiles don't block anything, empty decoration tiles are walkable.
+    pub fn new(decoration: &MapData<DecorationTile>, solid: &MapData<SolidTile>) -> Self {
+        let width = decoration.width();
+        let height = decoration.height();
+        let mut tiles = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                tiles.push(NavTile {
+                    solid: Self::blocks(solid.get(x, y)),
+                    road: decoration.get(x, y).is_some_and(TileClass::road),
+                    penalty: 0,
+                });
+            }
+        }
+        Self {
+            tiles,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns cost of entering the tile, or `None` if it can't be entered at all.
+    pub fn cost(&self, x: usize, y: usize, movement: Movement) -> Option<u32> {
+        self.tile(x, y).and_then(|tile| movement.cost(tile))
+    }
+
+    /// Checks whether tile can be entered using provided movement. Out of bounds tiles can't.
+    pub fn is_passable(&self, x: usize, y: usize, movement: Movement) -> bool {
+        self.cost(x, y, movement).is_some()
+    }
+
+    /// Iterates over all tiles that can be entered using provided movement.
+    pub fn passable(&self, movement: Movement) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+            .filter(move |&(x, y)| self.is_passable(x, y, movement))
+    }
+
+    /// Sets extra cost of entering the tile, for example to route around an obstacle.
+    pub fn set_penalty(&mut self, x: usize, y: usize, penalty: u32) -> Result<(), OutOfBounds> {
+        let tile = self.tile_mut(x, y).ok_or(OutOfBounds { x, y })?;
+        tile.penalty = penalty;
+        Ok(())
+    }
+
+    /// Applies changes of the solid layer. Changes outside of the grid are ignored.
+    pub fn update_solid<'map>(
+        &mut self,
+        changes: impl IntoIterator<Item = TileDiff<'map, SolidTile>>,
+    ) {
+        for change in changes {
+            if let Some(tile) = self.tile_mut(change.x, change.y) {
+                tile.solid = Self::blocks(change.after);
+            }
+        }
+    }
+
+    /// Applies changes of the decoration layer. Changes outside of the grid are ignored.
+    pub fn update_decoration<'map>(
+        &mut self,
+        changes: impl IntoIterator<Item = TileDiff<'map, DecorationTile>>,
+    ) {
+        for change in changes {
+            if let Some(tile) = self.tile_mut(change.x, change.y) {
+                tile.road = change.after.is_some_and(TileClass::road);
+            }
+        }
+    }
+
+    /// Finds the cheapest path between two tiles with A*.
+    ///
+    /// See [NavGrid::find_path_with] for details.
+    pub fn find_path(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        movement: Movement,
+    ) -> Option<Vec<(usize, usize)>> {
+        self.find_path_with(from, to, movement, Algorithm::AStar)
+    }
+
+    /// Finds path between two tiles.
+    ///
+    /// Returned path starts with `from`, ends with `to` and contains every tile in between.
+    /// `None` is returned if there is no path or either end isn't passable.
+    pub fn find_path_with(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        movement: Movement,
+        algorithm: Algorithm,
+    ) -> Option<Vec<(usize, usize)>> {
+        if !self.is_passable(from.0, from.1, movement) || !self.is_passable(to.0, to.1, movement) {
+            return None;
+        }
+        let waypoints = match algorithm {
+            Algorithm::AStar => self.search(from, to, |tile, cost| {
+                self.neighbours(tile, movement)
+                    .filter_map(|next| Some((next, cost + self.cost(next.0, next.1, movement)?)))
+                    .collect()
+            }),
+            Algorithm::JumpPoint => self.search(from, to, |tile, cost| {
+                self.jump_successors(tile, to, movement)
+                    .into_iter()
+                    .map(|next| (next, cost + distance(tile, next) as u32 * BASE_COST))
+                    .collect()
+            }),
+        }?;
+
+        // Jump points are connected with straight lines, which are filled in here.
+        let mut path = vec![from];
+        for &(x, y) in &waypoints[1..] {
+            while let Some(&last) = path.last() {
+                if last == (x, y) {
+                    break;
+                }
+                let step = |from: usize, to: usize| match from.cmp(&to) {
+                    std::cmp::Ordering::Less => from + 1,
+                    std::cmp::Ordering::Equal => from,
+                    std::cmp::Ordering::Greater => from - 1,
+                };
+                path.push((step(last.0, x), step(last.1, y)));
+            }
+        }
+        Some(path)
+    }
+
+    /// Best-first search that returns waypoints between `from` and `to`.
+    ///
+    /// `successors` returns tiles reachable from the provided tile together with the total
+    /// cost of reaching them.
+    fn search(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        successors: impl Fn((usize, usize), u32) -> Vec<((usize, usize), u32)>,
+    ) -> Option<Vec<(usize, usize)>> {
+        let index = |(x, y): (usize, usize)| x + y * self.width;
+        // Every tile costs at least the base cost, so the heuristic never overestimates.
+        let heuristic = |tile| distance(tile, to) as u32 * BASE_COST;
+
+        let mut cost = vec![u32::MAX; self.tiles.len()];
+        let mut came_from = vec![None; self.tiles.len()];
+        let mut open = BinaryHeap::new();
+        cost[index(from)] = 0;
+        open.push(Reverse((heuristic(from), from)));
+
+        while let Some(Reverse((_, current))) = open.pop() {
+            if current == to {
+                let mut path = vec![to];
+                let mut tile = to;
+                while let Some(previous) = came_from[index(tile)] {
+                    path.push(previous);
+                    tile = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for (next, next_cost) in successors(current, cost[index(current)]) {
+                if next_cost < cost[index(next)] {
+                    cost[index(next)] = next_cost;
+                    came_from[index(next)] = Some(current);
+                    open.push(Reverse((next_cost + heuristic(next), next)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns jump points reachable from the tile.
+    ///
+    /// Paths are canonically built by going vertically first, so vertical jumps look for
+    /// jump points on both sides, while horizontal jumps only stop next to an obstacle that
+    /// hides a tile from vertical jumps.
+    fn jump_successors(
+        &self,
+        tile: (usize, usize),
+        goal: (usize, usize),
+        movement: Movement,
+    ) -> Vec<(usize, usize)> {
+        [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .into_iter()
+            .filter_map(|direction| self.jump(tile, direction, goal, movement))
+            .collect()
+    }
+
+    fn jump(
+        &self,
+        from: (usize, usize),
+        (dx, dy): (isize, isize),
+        goal: (usize, usize),
+        movement: Movement,
+    ) -> Option<(usize, usize)> {
+        let passable = |x: usize, y: usize, ox: isize, oy: isize| {
+            let (Some(x), Some(y)) = (x.checked_add_signed(ox), y.checked_add_signed(oy)) else {
+                return false;
+            };
+            self.is_passable(x, y, movement)
+        };
+
+        let (mut x, mut y) = from;
+        loop {
+            x = x.checked_add_signed(dx)?;
+            y = y.checked_add_signed(dy)?;
+            if !self.is_passable(x, y, movement) {
+                return None;
+            }
+            if (x, y) == goal {
+                return Some((x, y));
+            }
+            if dx != 0 {
+                let forced = |oy| passable(x, y, 0, oy) && !passable(x, y, -dx, oy);
+                if forced(1) || forced(-1) {
+                    return Some((x, y));
+                }
+            } else if self.jump((x, y), (1, 0), goal, movement).is_some()
+                || self.jump((x, y), (-1, 0), goal, movement).is_some()
+            {
+                return Some((x, y));
+            }
+        }
+    }
+
+    fn blocks(tile: Option<&SolidTile>) -> bool {
+        tile.is_some_and(|tile| !tile.overhang())
+    }
+
+    fn tile(&self, x: usize, y: usize) -> Option<&NavTile> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.tiles.get(x + y * self.width)
+    }
+
+    fn tile_mut(&mut self, x: usize, y: usize) -> Option<&mut NavTile> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.tiles.get_mut(x + y * self.width)
+    }
+
+    fn neighbours(
+        &self,
+        (x, y): (usize, usize),
+        movement: Movement,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .into_iter()
+            .filter_map(move |(dx, dy)| {
+                Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?))
+            })
+            .filter(move |&(x, y)| self.is_passable(x, y, movement))
+    }
+}
+
+/// Manhattan distance between two tiles.
+fn distance(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
@@ -1,24 +1,45 @@
 use std::path::Path;
 
+use super::bounds::{Bounds, Location};
+use super::delivery::{DeliveryPoint, DeliveryPointKind};
 use super::tile::*;
 use super::MapData;
 use super::MapSaveError;
 
 pub fn generate_preset_maps() -> Result<(), MapSaveError> {
-    generate_decoration_map()?;
-    generate_solid_map()?;
-    Ok(())
+    decoration_map().save(Path::new("decoration.beer_map"))?;
+    solid_map().save(Path::new("solid.beer_map"))
 }
 
-fn generate_decoration_map() -> Result<(), MapSaveError> {
+/// Delivery points located on the preset maps.
+pub fn delivery_points() -> Vec<DeliveryPoint> {
+    vec![
+        DeliveryPoint {
+            kind: DeliveryPointKind::Brewery,
+            x: 7,
+            y: 7,
+        },
+        DeliveryPoint {
+            kind: DeliveryPointKind::Bar,
+            x: 1,
+            y: 3,
+        },
+        DeliveryPoint {
+            kind: DeliveryPointKind::Customer,
+            x: 13,
+            y: 2,
+        },
+    ]
+}
+
+/// Decoration layer of the preset map.
+pub fn decoration_map() -> MapData<DecorationTile> {
     let mut map = MapData::<DecorationTile>::new(16, 16, String::from("city_tiles.png"));
+    let bounds = map.bounds().unwrap();
+    map.fill(&bounds, Some(DecorationTile { texture: 28 }));
+    // Road across the whole map, under the brewery.
     for x in 0..16 {
-        for y in 0..16 {
-            *map.get_mut(x, y) = Some(DecorationTile { texture: 28 });
-        }
-    }
-    for x in 0..8 {
-        if x == 7 {
+        if x == 15 {
             *map.get_mut(x, 6) = Some(DecorationTile {
                 texture: 16 * 27 + 6,
             });
@@ -51,60 +72,12 @@ fn generate_decoration_map() -> Result<(), MapSaveError> {
         }
     }
 
-    map.save(Path::new("decoration.beer_map"))
+    map
 }
 
-fn generate_solid_map() -> Result<(), MapSaveError> {
-    let mut map = MapData::<DecorationTile>::new(16, 16, String::from("city_tiles.png"));
-
-    struct Bounds {
-        pub min_x: usize,
-        pub min_y: usize,
-        pub max_x: usize,
-        pub max_y: usize,
-    }
-
-    impl Bounds {
-        pub fn location(&self, x: usize, y: usize) -> Location {
-            if x == self.min_x && y == self.min_y {
-                return Location::BottomLeft;
-            }
-            if x == self.min_x && y == self.max_y {
-                return Location::TopLeft;
-            }
-            if x == self.max_x && y == self.min_y {
-                return Location::BottomRight;
-            }
-            if x == self.max_x && y == self.max_y {
-                return Location::TopRight;
-            }
-            if x == self.min_x {
-                return Location::Left;
-            }
-            if x == self.max_x {
-                return Location::Right;
-            }
-            if y == self.min_y {
-                return Location::Bottom;
-            }
-            if y == self.max_y {
-                return Location::Top;
-            }
-            Location::Center
-        }
-    }
-
-    pub enum Location {
-        Center,
-        Top,
-        Bottom,
-        Left,
-        Right,
-        TopLeft,
-        TopRight,
-        BottomLeft,
-        BottomRight,
-    }
+/// Solid layer of the preset map.
+pub fn solid_map() -> MapData<SolidTile> {
+    let mut map = MapData::<SolidTile>::new(16, 16, String::from("city_tiles.png"));
 
     let b = Bounds {
         min_x: 5,
@@ -127,7 +100,10 @@ fn generate_solid_map() -> Result<(), MapSaveError> {
                     Location::BottomRight => 5 * 27 + 10,
                 }
             };
-            *map.get_mut(x, y) = Some(DecorationTile { texture });
+            *map.get_mut(x, y) = Some(SolidTile {
+                texture,
+                overhang: y == b.max_y,
+            });
         }
     }
 
@@ -148,9 +124,12 @@ fn generate_solid_map() -> Result<(), MapSaveError> {
                 Location::BottomRight => 19 + 27 * 3,
                 _ => unreachable!(),
             };
-            *map.get_mut(x, y) = Some(DecorationTile { texture });
+            *map.get_mut(x, y) = Some(SolidTile {
+                texture,
+                overhang: false,
+            });
         }
     }
 
-    map.save(Path::new("solid.beer_map"))
+    map
 }
@@ -4,22 +4,51 @@ use serde::{Deserialize, Serialize};
 /// Every tile class is located in separate layer and has separate spritesheet.
 pub trait TileClass: Clone + Component {
     fn texture(&self) -> u32;
+
+    /// Whether the tile hangs above the ground and should be drawn above everything else.
+    fn overhang(&self) -> bool {
+        false
+    }
+
+    /// Whether vehicles that are restricted to roads are able to drive over the tile.
+    fn road(&self) -> bool {
+        false
+    }
+
+    /// Whether the tile is covered with grass.
+    fn grass(&self) -> bool {
+        false
+    }
+
+    /// Location that the tile leads to, if any.
+    fn destination(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// A tile that doesn't allow player to go through it.
-#[derive(Debug, Clone, Serialize, Deserialize, Component)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Component)]
 pub struct SolidTile {
-    texture: u32,
+    pub texture: u32,
+    /// Part of the building that hangs above the ground, like the upper edge of the roof.
+    ///
+    /// Overhang doesn't stop anything from going under it.
+    #[serde(default)]
+    pub overhang: bool,
 }
 
 impl TileClass for SolidTile {
     fn texture(&self) -> u32 {
         self.texture
     }
+
+    fn overhang(&self) -> bool {
+        self.overhang
+    }
 }
 
 /// A tile that only exists for decoration purposes.
-#[derive(Debug, Clone, Serialize, Deserialize, Component)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Component)]
 pub struct DecorationTile {
     pub texture: u32,
 }
@@ -28,10 +57,26 @@ impl TileClass for DecorationTile {
     fn texture(&self) -> u32 {
         self.texture
     }
+
+    fn road(&self) -> bool {
+        // Asphalt and road markings of `city_tiles.png`.
+        let (row, column) = (self.texture / 27, self.texture % 27);
+        match row {
+            15 => column <= 2,
+            16 | 17 => column <= 8,
+            _ => false,
+        }
+    }
+
+    fn grass(&self) -> bool {
+        // Lawn in the upper left corner of `city_tiles.png`.
+        let (row, column) = (self.texture / 27, self.texture % 27);
+        row <= 2 && column <= 2
+    }
 }
 
 /// A tile that allows player to move between scenes or locations.
-#[derive(Debug, Clone, Serialize, Deserialize, Component)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Component)]
 pub struct DoorTile {
     destination: String,
     texture: u32,
@@ -41,4 +86,8 @@ impl TileClass for DoorTile {
     fn texture(&self) -> u32 {
         self.texture
     }
+
+    fn destination(&self) -> Option<&str> {
+        Some(&self.destination)
+    }
 }
@@ -0,0 +1,125 @@
+//! Consistency checks of [MapData](super::MapData).
+//!
+//! Validation never fails by itself and instead reports every found problem as [Diagnostic].
+//! Checks that depend on things outside of the map, like the spritesheet or other layers,
+//! are only performed when enabled in [ValidationRules].
+
+use std::{collections::HashSet, fmt::Display};
+
+use bevy_ecs_tilemap::prelude::TilemapSize;
+
+/// Additional checks performed by [MapData::validate](super::MapData::validate).
+#[derive(Debug, Clone, Default)]
+pub struct ValidationRules {
+    texture_count: Option<u32>,
+    size: Option<TilemapSize>,
+    destinations: Option<HashSet<String>>,
+}
+
+impl ValidationRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires every texture index to be less than amount of textures in the spritesheet.
+    pub fn with_texture_count(mut self, texture_count: u32) -> Self {
+        self.texture_count = Some(texture_count);
+        self
+    }
+
+    /// Requires map to be of the same size as other layers.
+    pub fn with_size(mut self, size: TilemapSize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Requires every door to lead to one of provided locations.
+    pub fn with_destinations(mut self, destinations: impl IntoIterator<Item = String>) -> Self {
+        self.destinations = Some(destinations.into_iter().collect());
+        self
+    }
+
+    pub(super) fn texture_count(&self) -> Option<u32> {
+        self.texture_count
+    }
+
+    pub(super) fn size(&self) -> Option<TilemapSize> {
+        self.size
+    }
+
+    pub(super) fn destination_exists(&self, destination: &str) -> bool {
+        self.destinations
+            .as_ref()
+            .is_none_or(|destinations| destinations.contains(destination))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Map is usable, but probably not in the way it was intended.
+    Warning,
+    /// Map can't be used.
+    Error,
+}
+
+/// Problem found during validation of the map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub problem: Problem,
+    /// Position of the tile that caused the problem, if it is caused by a single tile.
+    pub position: Option<(usize, usize)>,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        self.problem.severity()
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity() == Severity::Error
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity() {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match self.position {
+            Some((x, y)) => write!(f, "{severity} at ({x}, {y}): {}", self.problem),
+            None => write!(f, "{severity}: {}", self.problem),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Problem {
+    #[error("map width is zero")]
+    ZeroWidth,
+    #[error("map has no tiles")]
+    Empty,
+    #[error("{tiles} tiles can't be split into rows of {width}")]
+    IncompleteRow { tiles: usize, width: usize },
+    #[error("texture {texture} is out of spritesheet with {texture_count} textures")]
+    TextureOutOfRange { texture: u32, texture_count: u32 },
+    #[error("map is {}x{}, while other layers are {}x{}", found.0, found.1, expected.0, expected.1)]
+    SizeMismatch {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    #[error("door leads to location `{0}` that doesn't exist")]
+    MissingDestination(String),
+}
+
+impl Problem {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Problem::Empty | Problem::MissingDestination(_) => Severity::Warning,
+            Problem::ZeroWidth
+            | Problem::IncompleteRow { .. }
+            | Problem::TextureOutOfRange { .. }
+            | Problem::SizeMismatch { .. } => Severity::Error,
+        }
+    }
+}
@@ -1,6 +1,20 @@
+use std::path::Path;
+
 use bevy::{app::AppExit, prelude::*};
 
-use crate::GameState;
+use crate::{
+    audio::{Channel, PlaySound, Sound, Volume},
+    game::{
+        arcade::{Arcade, SHIFT_LENGTH},
+        level::{describe_stars, CurrentLevel, Levels, Save},
+        map::MapSelection,
+        order::OrderPool,
+        player::Coop,
+        replay::{Playback, REPLAY_FILE},
+    },
+    replay::Replay,
+    GameState,
+};
 
 pub struct MenuPlugin;
 
@@ -8,9 +22,20 @@ impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<MenuState>()
             .configure_set(MenuSystemSet.run_if(in_state(GameState::MainMenu)))
-            .add_system(main_menu_setup.in_schedule(OnEnter(GameState::MainMenu)))
-            .add_system(main_menu_cleanup.in_schedule(OnExit(GameState::MainMenu)))
-            .add_systems((button_system, menu_action).in_set(MenuSystemSet));
+            .add_system(menu_setup.in_schedule(OnEnter(GameState::MainMenu)))
+            .add_system(main_menu_setup.in_schedule(OnEnter(MenuState::Main)))
+            .add_system(cleanup::<OnMainMenuScreen>.in_schedule(OnExit(MenuState::Main)))
+            .add_system(settings_menu_setup.in_schedule(OnEnter(MenuState::Settings)))
+            .add_system(cleanup::<OnSettingsMenuScreen>.in_schedule(OnExit(MenuState::Settings)))
+            .add_system(level_select_setup.in_schedule(OnEnter(MenuState::Levels)))
+            .add_system(cleanup::<OnLevelSelectScreen>.in_schedule(OnExit(MenuState::Levels)))
+            .add_system(arcade_menu_setup.in_schedule(OnEnter(MenuState::Arcade)))
+            .add_system(cleanup::<OnArcadeMenuScreen>.in_schedule(OnExit(MenuState::Arcade)))
+            .add_system(coop_menu_setup.in_schedule(OnEnter(MenuState::Coop)))
+            .add_system(cleanup::<OnCoopMenuScreen>.in_schedule(OnExit(MenuState::Coop)))
+            .add_system(high_scores_setup.in_schedule(OnEnter(MenuState::HighScores)))
+            .add_system(cleanup::<OnHighScoresScreen>.in_schedule(OnExit(MenuState::HighScores)))
+            .add_systems((button_system, menu_action, update_volume_text).in_set(MenuSystemSet));
     }
 }
 
@@ -21,6 +46,10 @@ struct MenuSystemSet;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, States, Hash, Default)]
 enum MenuState {
     Main,
+    Levels,
+    Arcade,
+    Coop,
+    HighScores,
     Settings,
     #[default]
     Disabled,
@@ -30,10 +59,47 @@ enum MenuState {
 #[derive(Component)]
 struct OnMainMenuScreen;
 
+// Tag component used to tag entities added on the settings screen
+#[derive(Component)]
+struct OnSettingsMenuScreen;
+
+// Tag component used to tag entities added on the level select screen
+#[derive(Component)]
+struct OnLevelSelectScreen;
+
+// Tag component used to tag entities added on the arcade map select screen
+#[derive(Component)]
+struct OnArcadeMenuScreen;
+
+// Tag component used to tag entities added on the co-op screen
+#[derive(Component)]
+struct OnCoopMenuScreen;
+
+// Tag component used to tag entities added on the high scores screen
+#[derive(Component)]
+struct OnHighScoresScreen;
+
+/// Text that shows volume of the channel.
+#[derive(Component)]
+struct VolumeText(Channel);
+
+/// Step by which volume buttons change the volume.
+const VOLUME_STEP: f32 = 0.1;
+
+/// Amount of the best scores of every table that are shown in the menu.
+const SHOWN_HIGH_SCORES: usize = 3;
+
+/// Width of the buttons with labels that don't fit into the usual ones.
+const WIDE_BUTTON: f32 = 400.0;
+
+/// Font used for all text in the game.
+pub const FONT: &str = "fonts/FiraMono-Medium.ttf";
+
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const HOVERED_PRESSED_BUTTON: Color = Color::rgb(0.25, 0.65, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+const LOCKED_BUTTON: Color = Color::rgb(0.08, 0.08, 0.08);
 
 // Tag component used to mark which setting is currently selected
 #[derive(Component)]
@@ -43,18 +109,48 @@ struct SelectedOption;
 #[derive(Component)]
 enum MenuButtonAction {
     Play,
+    /// Starts level with provided index in the district with provided index.
+    StartLevel(usize, usize),
+    RandomCity,
+    Arcade,
+    StartArcade(ArcadeMap),
+    Coop,
+    /// Starts co-op shift in a random city with provided order pool.
+    StartCoop(OrderPool),
+    /// Plays the recording of the last game.
+    WatchReplay,
+    HighScores,
     Settings,
+    ChangeVolume(Channel, f32),
+    BackToMainMenu,
     Quit,
 }
 
+/// Map that the arcade shift is played on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArcadeMap {
+    Preset,
+    RandomCity,
+}
+
 // This system handles changing all buttons color based on mouse interaction
 fn button_system(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
         (Changed<Interaction>, With<Button>),
     >,
+    mouse_input: Res<Input<MouseButton>>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
     for (interaction, mut color, selected) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => sounds.send(PlaySound(Sound::Click)),
+            // Button is hovered again once it's released.
+            Interaction::Hovered if !mouse_input.just_released(MouseButton::Left) => {
+                sounds.send(PlaySound(Sound::Hover))
+            }
+            _ => (),
+        }
         *color = match (*interaction, selected) {
             (Interaction::Clicked, _) | (Interaction::None, Some(_)) => PRESSED_BUTTON.into(),
             (Interaction::Hovered, Some(_)) => HOVERED_PRESSED_BUTTON.into(),
@@ -64,6 +160,10 @@ fn button_system(
     }
 }
 
+fn menu_setup(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Main);
+}
+
 fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
         .spawn((
@@ -81,29 +181,353 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         ))
         .with_children(|parent| {
             spawn_button(parent, &asset_server, "Play", MenuButtonAction::Play);
+            spawn_sized_button(
+                parent,
+                &asset_server,
+                "New random city",
+                MenuButtonAction::RandomCity,
+                WIDE_BUTTON,
+            );
+            spawn_button(parent, &asset_server, "Arcade", MenuButtonAction::Arcade);
+            spawn_button(parent, &asset_server, "Co-op", MenuButtonAction::Coop);
+            spawn_sized_button(
+                parent,
+                &asset_server,
+                "Watch replay",
+                MenuButtonAction::WatchReplay,
+                WIDE_BUTTON,
+            );
+            spawn_sized_button(
+                parent,
+                &asset_server,
+                "High scores",
+                MenuButtonAction::HighScores,
+                WIDE_BUTTON,
+            );
             spawn_button(parent, &asset_server, "Settings", MenuButtonAction::Settings);
             spawn_button(parent, &asset_server, "Quit", MenuButtonAction::Quit);
         });
 }
 
+fn settings_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            OnSettingsMenuScreen,
+        ))
+        .with_children(|parent| {
+            for channel in Channel::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        spawn_sized_button(
+                            parent,
+                            &asset_server,
+                            "-",
+                            MenuButtonAction::ChangeVolume(channel, -VOLUME_STEP),
+                            65.0,
+                        );
+                        parent
+                            .spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        size: Size::width(Val::Px(270.0)),
+                                        justify_content: JustifyContent::Center,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                VolumeText(channel),
+                            ))
+                            .with_children(|parent| spawn_text(parent, &asset_server, ""));
+                        spawn_sized_button(
+                            parent,
+                            &asset_server,
+                            "+",
+                            MenuButtonAction::ChangeVolume(channel, VOLUME_STEP),
+                            65.0,
+                        );
+                    });
+            }
+            spawn_button(
+                parent,
+                &asset_server,
+                "Back",
+                MenuButtonAction::BackToMainMenu,
+            );
+        });
+}
+
+fn level_select_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    levels: Res<Levels>,
+    save: Res<Save>,
+) {
+    let campaign = &levels.0;
+    let progress = &save.0.campaign;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            OnLevelSelectScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                for (d, district) in campaign.districts.iter().enumerate() {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            spawn_text(parent, &asset_server, &district.name);
+                            for (l, level) in district.levels.iter().enumerate() {
+                                if !campaign.is_level_unlocked(progress, d, l) {
+                                    spawn_locked_button(parent, &asset_server, 480.0);
+                                    continue;
+                                }
+                                let stars = progress.stars.get(&level.id).copied();
+                                let text = format!(
+                                    "{} {}",
+                                    level.name,
+                                    describe_stars(stars.unwrap_or(0), level.max_stars())
+                                );
+                                spawn_sized_button(
+                                    parent,
+                                    &asset_server,
+                                    &text,
+                                    MenuButtonAction::StartLevel(d, l),
+                                    480.0,
+                                );
+                            }
+                        });
+                }
+            });
+            spawn_button(
+                parent,
+                &asset_server,
+                "Back",
+                MenuButtonAction::BackToMainMenu,
+            );
+        });
+}
+
+fn arcade_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            OnArcadeMenuScreen,
+        ))
+        .with_children(|parent| {
+            let goal = format!("Earn as much as you can in {} minutes", SHIFT_LENGTH / 60.0);
+            spawn_text(parent, &asset_server, &goal);
+            spawn_sized_button(
+                parent,
+                &asset_server,
+                "Preset map",
+                MenuButtonAction::StartArcade(ArcadeMap::Preset),
+                WIDE_BUTTON,
+            );
+            spawn_sized_button(
+                parent,
+                &asset_server,
+                "Random city",
+                MenuButtonAction::StartArcade(ArcadeMap::RandomCity),
+                WIDE_BUTTON,
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                "Back",
+                MenuButtonAction::BackToMainMenu,
+            );
+        });
+}
+
+fn coop_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            OnCoopMenuScreen,
+        ))
+        .with_children(|parent| {
+            spawn_text(
+                parent,
+                &asset_server,
+                "Player 1: WASD, LShift to run, E to interact",
+            );
+            spawn_text(
+                parent,
+                &asset_server,
+                "Player 2: arrows, RShift to run, RControl to interact",
+            );
+            spawn_sized_button(
+                parent,
+                &asset_server,
+                "Shared orders",
+                MenuButtonAction::StartCoop(OrderPool::Shared),
+                WIDE_BUTTON,
+            );
+            spawn_sized_button(
+                parent,
+                &asset_server,
+                "Competitive",
+                MenuButtonAction::StartCoop(OrderPool::Competitive),
+                WIDE_BUTTON,
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                "Back",
+                MenuButtonAction::BackToMainMenu,
+            );
+        });
+}
+
+fn high_scores_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    save: Res<Save>,
+    levels: Res<Levels>,
+) {
+    let tables = &save.0.leaderboard.tables;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            OnHighScoresScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    if tables.is_empty() {
+                        spawn_text(parent, &asset_server, "No shifts finished yet");
+                    }
+                    for (table, scores) in tables {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    margin: UiRect::all(Val::Px(20.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                // Tables of the levels are kept by their id.
+                                let name = levels
+                                    .0
+                                    .levels()
+                                    .find(|level| level.id == *table)
+                                    .map_or(table, |level| &level.name);
+                                spawn_text(parent, &asset_server, name);
+                                for (rank, high_score) in
+                                    scores.iter().take(SHOWN_HIGH_SCORES).enumerate()
+                                {
+                                    let stats = &high_score.stats;
+                                    let text = format!(
+                                        "{}. {:>6} ({}/{} on time)",
+                                        rank + 1,
+                                        high_score.score,
+                                        stats.deliveries.saturating_sub(stats.late),
+                                        stats.deliveries
+                                    );
+                                    spawn_text(parent, &asset_server, &text);
+                                }
+                            });
+                    }
+                });
+            spawn_button(
+                parent,
+                &asset_server,
+                "Back",
+                MenuButtonAction::BackToMainMenu,
+            );
+        });
+}
+
 fn spawn_button(
     parent: &mut ChildBuilder,
     asset_server: &Res<AssetServer>,
     text: &str,
     action: MenuButtonAction,
 ) {
-    let button_style = Style {
-        size: Size::new(Val::Px(250.0), Val::Px(65.0)),
-        margin: UiRect::all(Val::Px(20.0)),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        ..default()
-    };
+    spawn_sized_button(parent, asset_server, text, action, 250.0);
+}
 
+fn spawn_sized_button(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    text: &str,
+    action: MenuButtonAction,
+    width: f32,
+) {
     parent
         .spawn((
             ButtonBundle {
-                style: button_style,
+                style: button_style(width),
                 background_color: NORMAL_BUTTON.into(),
                 ..Default::default()
             },
@@ -112,8 +536,29 @@ fn spawn_button(
         .with_children(|parent| spawn_text(parent, asset_server, text));
 }
 
+/// Spawns placeholder of the button that can't be pressed yet.
+fn spawn_locked_button(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>, width: f32) {
+    parent
+        .spawn(NodeBundle {
+            style: button_style(width),
+            background_color: LOCKED_BUTTON.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| spawn_text(parent, asset_server, "Locked"));
+}
+
+fn button_style(width: f32) -> Style {
+    Style {
+        size: Size::new(Val::Px(width), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
 fn spawn_text(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>, text: &str) {
-    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let font = asset_server.load(FONT);
     let button_text_style = TextStyle {
         font,
         font_size: 40.0,
@@ -123,6 +568,7 @@ fn spawn_text(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>, text:
     parent.spawn(TextBundle::from_section(text, button_text_style));
 }
 
+#[allow(clippy::too_many_arguments)]
 fn menu_action(
     interaction_query: Query<
         (&Interaction, &MenuButtonAction),
@@ -131,21 +577,103 @@ fn menu_action(
     mut app_exit_events: EventWriter<AppExit>,
     mut menu_state: ResMut<NextState<MenuState>>,
     mut game_state: ResMut<NextState<GameState>>,
+    mut map_selection: ResMut<MapSelection>,
+    mut volume: ResMut<Volume>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut arcade: ResMut<Arcade>,
+    mut coop: ResMut<Coop>,
+    mut save: ResMut<Save>,
+    levels: Res<Levels>,
+    mut commands: Commands,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Clicked {
             match menu_button_action {
                 MenuButtonAction::Quit => app_exit_events.send(AppExit),
-                MenuButtonAction::Play => {
+                MenuButtonAction::Play => menu_state.set(MenuState::Levels),
+                &MenuButtonAction::StartLevel(district, level) => {
+                    let level = levels.0.districts[district].levels[level].clone();
+                    *map_selection = level.map.into();
+                    current_level.0 = Some(level);
+                    arcade.0 = false;
+                    coop.0 = None;
+                    game_state.set(GameState::Game);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::RandomCity => {
+                    current_level.0 = None;
+                    *map_selection = MapSelection::Random(rand::random());
+                    arcade.0 = false;
+                    coop.0 = None;
+                    game_state.set(GameState::Game);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::Arcade => menu_state.set(MenuState::Arcade),
+                MenuButtonAction::StartArcade(map) => {
+                    current_level.0 = None;
+                    *map_selection = match map {
+                        ArcadeMap::Preset => MapSelection::Preset,
+                        ArcadeMap::RandomCity => MapSelection::Random(rand::random()),
+                    };
+                    arcade.0 = true;
+                    coop.0 = None;
+                    game_state.set(GameState::Game);
+                    menu_state.set(MenuState::Disabled);
+                }
+                MenuButtonAction::Coop => menu_state.set(MenuState::Coop),
+                &MenuButtonAction::StartCoop(pool) => {
+                    current_level.0 = None;
+                    *map_selection = MapSelection::Random(rand::random());
+                    arcade.0 = false;
+                    coop.0 = Some(pool);
                     game_state.set(GameState::Game);
                     menu_state.set(MenuState::Disabled);
                 }
+                MenuButtonAction::WatchReplay => match Replay::load(Path::new(REPLAY_FILE)) {
+                    Ok(replay) => {
+                        commands.insert_resource(Playback::start(
+                            replay,
+                            &mut save,
+                            &mut map_selection,
+                            &mut current_level,
+                            &mut arcade,
+                            &mut coop,
+                        ));
+                        game_state.set(GameState::Game);
+                        menu_state.set(MenuState::Disabled);
+                    }
+                    Err(err) => warn!("{err}"),
+                },
+                MenuButtonAction::HighScores => menu_state.set(MenuState::HighScores),
                 MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
+                MenuButtonAction::ChangeVolume(channel, amount) => {
+                    volume.change(*channel, *amount);
+                    save.0.volume = *volume;
+                    save.write();
+                }
+                MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
+            }
+        }
+    }
+}
+
+/// Shows volume of every channel, including the one that is set right now.
+fn update_volume_text(
+    volume: Res<Volume>,
+    texts: Query<(&VolumeText, &Children)>,
+    mut sections: Query<&mut Text>,
+) {
+    for (VolumeText(channel), children) in &texts {
+        let mut texts = sections.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if volume.is_changed() || text.is_added() {
+                let percent = (volume.get(*channel) * 100.).round();
+                text.sections[0].value = format!("{}: {percent}%", channel.name());
             }
         }
     }
 }
 
-fn main_menu_cleanup(entities: Query<Entity, With<OnMainMenuScreen>>, mut commands: Commands) {
+fn cleanup<T: Component>(entities: Query<Entity, With<T>>, mut commands: Commands) {
     entities.for_each(|entity| commands.entity(entity).despawn_recursive());
 }
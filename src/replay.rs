@@ -0,0 +1,175 @@
+//! Recorded game sessions.
+//!
+//! Game is simulated in fixed steps, and the only things that differ between two sessions
+//! that start the same way are the [Actions] of the players in every step and the seed of the
+//! random number generator. [Replay] keeps both along with the [Session] settings, so the
+//! session can be played again exactly as it happened. Actions are stored as runs of equal
+//! steps, since players hold the same keys for many steps in a row.
+
+use std::{
+    ops::{BitOr, BitOrAssign},
+    path::Path,
+};
+
+use rmp_serde::{decode, encode};
+use serde::{Deserialize, Serialize};
+
+use crate::{campaign::Level, save::SaveGame};
+
+/// Set of the actions that a player performs during a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Actions(u8);
+
+impl Actions {
+    pub const NONE: Actions = Actions(0);
+    pub const UP: Actions = Actions(1 << 0);
+    pub const DOWN: Actions = Actions(1 << 1);
+    pub const LEFT: Actions = Actions(1 << 2);
+    pub const RIGHT: Actions = Actions(1 << 3);
+    pub const RUN: Actions = Actions(1 << 4);
+    pub const INTERACT: Actions = Actions(1 << 5);
+    /// Confirms the choice in a dialogue.
+    pub const CONFIRM: Actions = Actions(1 << 6);
+
+    /// Checks whether all provided actions are performed.
+    pub const fn contains(self, actions: Actions) -> bool {
+        self.0 & actions.0 == actions.0
+    }
+
+    /// Checks whether any of provided actions is performed.
+    pub const fn intersects(self, actions: Actions) -> bool {
+        self.0 & actions.0 != 0
+    }
+
+    /// Returns actions that are performed now but weren't performed in the previous step.
+    pub const fn started_since(self, previous: Actions) -> Actions {
+        Actions(self.0 & !previous.0)
+    }
+}
+
+impl BitOr for Actions {
+    type Output = Actions;
+
+    fn bitor(self, rhs: Actions) -> Actions {
+        Actions(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Actions {
+    fn bitor_assign(&mut self, rhs: Actions) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Map that the session is played on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayMap {
+    Preset,
+    /// Procedurally generated city of the default size.
+    Random(u64),
+    City {
+        seed: u64,
+        blocks_x: usize,
+        blocks_y: usize,
+    },
+}
+
+/// How the orders were split between the co-op couriers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayCoop {
+    Shared,
+    Competitive,
+}
+
+/// Settings that the session was started with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub map: ReplayMap,
+    /// Campaign level, or `None` outside of the campaign.
+    pub level: Option<Level>,
+    pub arcade: bool,
+    pub coop: Option<ReplayCoop>,
+    /// Progress of the player at the start of the session, which decides the unlocked vehicles
+    /// and the reputation.
+    pub save: SaveGame,
+}
+
+/// Steps with the same actions of every player.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct InputRun {
+    steps: u32,
+    actions: Vec<Actions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub session: Session,
+    /// Seed of the random number generator of the game.
+    pub seed: u64,
+    runs: Vec<InputRun>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayLoadError {
+    #[error("couldn't load replay file: {0}")]
+    FileSystemError(#[from] std::io::Error),
+    #[error("provided file is not in valid format: {0}")]
+    DecodeError(#[from] decode::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplaySaveError {
+    #[error("couldn't write replay file: {0}")]
+    FileSystemError(#[from] std::io::Error),
+    #[error("replay could not be encoded: {0}")]
+    EncodeError(#[from] encode::Error),
+}
+
+impl Replay {
+    /// Starts recording of the session without any steps.
+    pub fn new(session: Session, seed: u64) -> Self {
+        Self {
+            session,
+            seed,
+            runs: Vec::new(),
+        }
+    }
+
+    /// Adds the next step with provided actions of every player.
+    pub fn push(&mut self, actions: &[Actions]) {
+        match self.runs.last_mut() {
+            Some(run) if run.actions == actions && run.steps < u32::MAX => run.steps += 1,
+            _ => self.runs.push(InputRun {
+                steps: 1,
+                actions: actions.to_vec(),
+            }),
+        }
+    }
+
+    /// Returns actions of every player in every step, in order.
+    pub fn steps(&self) -> impl Iterator<Item = &[Actions]> {
+        self.runs
+            .iter()
+            .flat_map(|run| std::iter::repeat_n(run.actions.as_slice(), run.steps as usize))
+    }
+
+    /// Amount of recorded steps.
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|run| run.steps as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayLoadError> {
+        let bytes = std::fs::read(path)?;
+        Ok(decode::from_slice(&bytes)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplaySaveError> {
+        std::fs::write(path, encode::to_vec(self)?)?;
+        Ok(())
+    }
+}
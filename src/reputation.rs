@@ -0,0 +1,112 @@
+//! Reputation of the courier among the customers.
+//!
+//! Every customer and every district has a score from 0 to [MAX_SCORE], which starts at
+//! [NEUTRAL_SCORE]. Outcome of every order changes the score of its recipient and, to a lesser
+//! degree, of the whole district. Scores decide how often customers order, how much they tip
+//! and whether the bars of the district order at all.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+pub const MAX_SCORE: f32 = 100.;
+pub const NEUTRAL_SCORE: f32 = 50.;
+/// District score below which bars stop ordering.
+pub const BAR_SCORE: f32 = 40.;
+/// Customer score from which bars always place the largest orders.
+pub const BULK_ORDER_SCORE: f32 = 75.;
+/// Part of the customer score change that is applied to the district.
+const DISTRICT_SHARE: f32 = 0.4;
+/// Least likely order from the customer with the worst score, relative to a neutral one.
+const MIN_ORDER_WEIGHT: f32 = 0.25;
+
+/// How the order ended for its recipient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Delivered {
+        late: bool,
+        /// Amount of items that broke on the way.
+        broken: u32,
+    },
+    Cancelled,
+}
+
+impl Outcome {
+    /// Change of the recipient's score.
+    pub fn change(&self) -> f32 {
+        match *self {
+            Outcome::Delivered {
+                late: false,
+                broken: 0,
+            } => 10.,
+            Outcome::Delivered { late, broken } => {
+                let lateness = if late { -8. } else { 0. };
+                lateness - (4. * broken as f32).min(20.)
+            }
+            Outcome::Cancelled => -15.,
+        }
+    }
+}
+
+/// Scores in every district the courier has worked in.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reputation {
+    pub districts: BTreeMap<String, DistrictReputation>,
+}
+
+impl Reputation {
+    /// Returns reputation in the district, which is neutral in a new one.
+    pub fn district(&self, name: &str) -> DistrictReputation {
+        self.districts.get(name).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DistrictReputation {
+    pub score: f32,
+    /// Scores of the customers on every map of the district by the tile of their door.
+    #[serde(default)]
+    pub maps: BTreeMap<String, BTreeMap<(usize, usize), f32>>,
+}
+
+impl Default for DistrictReputation {
+    fn default() -> Self {
+        Self {
+            score: NEUTRAL_SCORE,
+            maps: BTreeMap::new(),
+        }
+    }
+}
+
+impl DistrictReputation {
+    pub fn customer(&self, map: &str, door: (usize, usize)) -> f32 {
+        self.maps
+            .get(map)
+            .and_then(|customers| customers.get(&door))
+            .copied()
+            .unwrap_or(NEUTRAL_SCORE)
+    }
+
+    /// Applies outcome of the order for the customer at provided door of the map.
+    pub fn record(&mut self, map: &str, door: (usize, usize), outcome: Outcome) {
+        let change = outcome.change();
+        let customer = self
+            .maps
+            .entry(String::from(map))
+            .or_default()
+            .entry(door)
+            .or_insert(NEUTRAL_SCORE);
+        *customer = (*customer + change).clamp(0., MAX_SCORE);
+        self.score = (self.score + change * DISTRICT_SHARE).clamp(0., MAX_SCORE);
+    }
+}
+
+/// Relative chance that the customer with provided score places the next order.
+pub fn order_weight(score: f32) -> f32 {
+    MIN_ORDER_WEIGHT + (1. - MIN_ORDER_WEIGHT) * score / NEUTRAL_SCORE
+}
+
+/// Multiplier of the tip given by the customer with provided score.
+pub fn tip_multiplier(score: f32) -> f32 {
+    score / NEUTRAL_SCORE
+}
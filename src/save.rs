@@ -0,0 +1,53 @@
+//! Progress of the player that is kept between the sessions.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{audio::Volume, campaign::Progress, reputation::Reputation, score::Leaderboard};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    #[serde(default)]
+    pub campaign: Progress,
+    #[serde(default)]
+    pub reputation: Reputation,
+    #[serde(default)]
+    pub leaderboard: Leaderboard,
+    #[serde(default)]
+    pub volume: Volume,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SaveLoadError {
+    #[error("couldn't load save file: {0}")]
+    FileSystemError(#[from] std::io::Error),
+    #[error("provided file is not in valid format: {0}")]
+    DecodeError(#[from] ron::error::SpannedError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SaveError {
+    #[error("couldn't write save file: {0}")]
+    FileSystemError(#[from] std::io::Error),
+    #[error("progress could not be encoded: {0}")]
+    EncodeError(#[from] ron::Error),
+}
+
+impl SaveGame {
+    /// Loads the save file, or starts from scratch if there is none yet.
+    pub fn load(path: &Path) -> Result<Self, SaveLoadError> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
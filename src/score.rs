@@ -0,0 +1,104 @@
+//! Scores of the shifts and the local leaderboard.
+//!
+//! Every shift is scored by its [LevelStats]. Deliveries give points, with a bonus for the
+//! ones made in time, earnings give a point per dollar, and broken items and cancelled orders
+//! cost points. Arcade shifts are only scored by the earnings. Best scores are kept in a
+//! [Leaderboard] with a table per level or map.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::campaign::LevelStats;
+
+/// Amount of scores kept in every table.
+pub const TABLE_SIZE: usize = 10;
+const DELIVERY_POINTS: i64 = 100;
+const ON_TIME_POINTS: i64 = 50;
+const BROKEN_PENALTY: i64 = 25;
+const CANCELLATION_PENALTY: i64 = 50;
+const CENTS_PER_POINT: i64 = 100;
+
+/// Points of the shift, split by where they came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Score {
+    pub deliveries: i64,
+    pub on_time: i64,
+    pub earnings: i64,
+    /// Penalty for the broken items, which is never positive.
+    pub breakage: i64,
+    /// Penalty for the cancelled orders, which is never positive.
+    pub cancellations: i64,
+}
+
+impl Score {
+    pub fn new(stats: &LevelStats) -> Self {
+        let on_time = stats.deliveries.saturating_sub(stats.late);
+        Self {
+            deliveries: stats.deliveries as i64 * DELIVERY_POINTS,
+            on_time: on_time as i64 * ON_TIME_POINTS,
+            earnings: stats.earnings / CENTS_PER_POINT,
+            breakage: -(stats.broken as i64) * BROKEN_PENALTY,
+            cancellations: -(stats.cancellations as i64) * CANCELLATION_PENALTY,
+        }
+    }
+
+    /// Sum of all the points, which is never below zero.
+    pub fn total(&self) -> u32 {
+        let sum =
+            self.deliveries + self.on_time + self.earnings + self.breakage + self.cancellations;
+        sum.clamp(0, u32::MAX as i64) as u32
+    }
+}
+
+/// How the shift is scored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scoring {
+    /// Every part of the [Score] counts.
+    Shift,
+    /// Only the earnings count, a point per dollar.
+    Earnings,
+}
+
+impl Scoring {
+    pub fn score(&self, stats: &LevelStats) -> u32 {
+        match self {
+            Scoring::Shift => Score::new(stats).total(),
+            Scoring::Earnings => Score {
+                earnings: Score::new(stats).earnings,
+                ..Default::default()
+            }
+            .total(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u32,
+    pub stats: LevelStats,
+}
+
+/// Best scores of every level by its id, or of the map outside of the campaign by its name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub tables: BTreeMap<String, Vec<HighScore>>,
+}
+
+impl Leaderboard {
+    /// Adds results of the shift to the table.
+    ///
+    /// Returns position in the table starting from 0, or `None` if the score is too low to
+    /// get into it. Older scores stay above the new ones that are equal to them.
+    pub fn record(&mut self, table: &str, scoring: Scoring, stats: LevelStats) -> Option<usize> {
+        let score = scoring.score(&stats);
+        let table = self.tables.entry(String::from(table)).or_default();
+        let position = table.partition_point(|high_score| high_score.score >= score);
+        if position >= TABLE_SIZE {
+            return None;
+        }
+        table.insert(position, HighScore { score, stats });
+        table.truncate(TABLE_SIZE);
+        Some(position)
+    }
+}
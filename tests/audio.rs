@@ -0,0 +1,50 @@
+use beer_delivery::{
+    audio::{Channel, Volume},
+    save::SaveGame,
+};
+
+#[test]
+fn volume_stays_in_range_and_on_round_steps() {
+    let mut volume = Volume::default();
+    for _ in 0..30 {
+        volume.change(Channel::Music, 0.1);
+    }
+    assert_eq!(volume.music, 1.);
+    for _ in 0..3 {
+        volume.change(Channel::Music, -0.1);
+    }
+    assert_eq!(volume.music, 0.7);
+    for _ in 0..30 {
+        volume.change(Channel::Effects, -0.1);
+    }
+    assert_eq!(volume.effects, 0.);
+}
+
+#[test]
+fn master_volume_scales_the_other_channels() {
+    let volume = Volume {
+        master: 0.5,
+        music: 0.6,
+        effects: 1.,
+    };
+    assert_eq!(volume.level(Channel::Master), 0.5);
+    assert!((volume.level(Channel::Music) - 0.3).abs() < 1e-6);
+    assert_eq!(volume.level(Channel::Effects), 0.5);
+}
+
+#[test]
+fn volume_is_kept_in_the_save() {
+    let save = SaveGame {
+        volume: Volume {
+            master: 0.3,
+            music: 0.,
+            effects: 0.9,
+        },
+        ..Default::default()
+    };
+    let text = ron::to_string(&save).unwrap();
+    assert_eq!(ron::from_str::<SaveGame>(&text).unwrap(), save);
+    // Saves from before the volume settings keep the default volume.
+    let old: SaveGame = ron::from_str("()").unwrap();
+    assert_eq!(old.volume, Volume::default());
+}
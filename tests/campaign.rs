@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use beer_delivery::{
+    campaign::{
+        Campaign, CampaignError, District, Level, LevelMap, LevelStats, Objective, Progress,
+        Unlock, Vehicle,
+    },
+    save::SaveGame,
+};
+
+fn level(id: &str, unlocks: Vec<Unlock>) -> Level {
+    Level {
+        id: String::from(id),
+        name: String::from(id),
+        map: LevelMap::Preset,
+        closing_hour: 18,
+        objectives: vec![Objective::Deliveries(3)],
+        stars: vec![Objective::Earnings(1000), Objective::MaxBroken(0)],
+        unlocks,
+    }
+}
+
+fn two_districts() -> Campaign {
+    Campaign {
+        vehicles: vec![Vehicle::Bike],
+        districts: vec![
+            District {
+                name: String::from("Old Town"),
+                levels: vec![
+                    level("first", vec![Unlock::Vehicle(Vehicle::Van)]),
+                    level("second", vec![Unlock::District(String::from("Harbour"))]),
+                ],
+            },
+            District {
+                name: String::from("Harbour"),
+                levels: vec![level("docks", Vec::new())],
+            },
+        ],
+    }
+}
+
+#[test]
+fn bundled_campaign_is_valid() {
+    let campaign = Campaign::load(Path::new("assets/campaign.ron")).unwrap();
+    assert!(campaign.levels().count() > 1);
+    assert!(campaign.is_level_unlocked(&Progress::default(), 0, 0));
+}
+
+#[test]
+fn invalid_campaign_is_reported() {
+    let mut campaign = two_districts();
+    campaign.districts[1]
+        .levels
+        .push(level("first", Vec::new()));
+    campaign.districts[1].levels[0]
+        .unlocks
+        .push(Unlock::District(String::from("Suburbs")));
+    assert_eq!(
+        campaign.validate(),
+        vec![
+            CampaignError::UnknownDistrict {
+                level: String::from("docks"),
+                district: String::from("Suburbs"),
+            },
+            CampaignError::DuplicateLevel(String::from("first")),
+        ]
+    );
+}
+
+#[test]
+fn stars_require_completed_level() {
+    let level = level("first", Vec::new());
+    let mut stats = LevelStats {
+        deliveries: 2,
+        earnings: 5000,
+        ..Default::default()
+    };
+    assert_eq!(level.stars(&stats), 0);
+
+    stats.deliveries = 3;
+    assert_eq!(level.stars(&stats), 3);
+    stats.broken = 1;
+    assert_eq!(level.stars(&stats), 2);
+    assert_eq!(level.max_stars(), 3);
+}
+
+#[test]
+fn completed_levels_unlock_content() {
+    let campaign = two_districts();
+    let mut progress = Progress::default();
+    assert!(!campaign.is_level_unlocked(&progress, 0, 1));
+    assert!(!campaign.is_level_unlocked(&progress, 1, 0));
+    assert_eq!(
+        campaign.unlocked_vehicles(&progress),
+        [Vehicle::Bike].into()
+    );
+
+    progress.record("first", 1);
+    assert!(campaign.is_level_unlocked(&progress, 0, 1));
+    assert!(!campaign.is_level_unlocked(&progress, 1, 0));
+    assert_eq!(
+        campaign.unlocked_vehicles(&progress),
+        [Vehicle::Bike, Vehicle::Van].into()
+    );
+
+    progress.record("second", 2);
+    progress.record("second", 1);
+    assert!(campaign.is_level_unlocked(&progress, 1, 0));
+    assert_eq!(progress.stars["second"], 2);
+}
+
+#[test]
+fn save_game_round_trips() {
+    let path = std::env::temp_dir().join("beer_delivery_campaign_save.ron");
+    let mut save = SaveGame::default();
+    save.campaign.record("first", 3);
+    save.save(&path).unwrap();
+    assert_eq!(SaveGame::load(&path).unwrap(), save);
+
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(SaveGame::load(&path).unwrap(), SaveGame::default());
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use beer_delivery::dialogue::{
+    Choice, Condition, Dialogue, DialogueError, DialogueSet, DialogueState, Node,
+};
+
+fn choice(text: &str, conditions: Vec<Condition>, next: Option<&str>) -> Choice {
+    Choice {
+        text: String::from(text),
+        conditions,
+        effects: Vec::new(),
+        next: next.map(String::from),
+    }
+}
+
+fn single_node(choices: Vec<Choice>) -> DialogueSet {
+    let node = Node {
+        speaker: String::from("Boss"),
+        text: String::from("Hi"),
+        choices,
+    };
+    let dialogue = Dialogue {
+        start: String::from("start"),
+        nodes: [(String::from("start"), node)].into(),
+    };
+    DialogueSet {
+        dialogues: [(String::from("boss"), dialogue)].into(),
+    }
+}
+
+#[test]
+fn bundled_dialogues_are_valid() {
+    let set = DialogueSet::load(Path::new("assets/dialogues.ron")).unwrap();
+    for name in ["boss", "customer", "bartender"] {
+        assert!(set.dialogues.contains_key(name), "{name} is missing");
+    }
+}
+
+#[test]
+fn missing_nodes_are_reported() {
+    let set = single_node(vec![choice("Bye", vec![], Some("nowhere"))]);
+    assert_eq!(
+        set.validate(),
+        vec![DialogueError::MissingNode {
+            dialogue: String::from("boss"),
+            node: String::from("nowhere"),
+        }]
+    );
+    assert!(single_node(vec![choice("Bye", vec![], None)])
+        .validate()
+        .is_empty());
+}
+
+#[test]
+fn conditions_filter_choices() {
+    let set = single_node(vec![
+        choice("Work?", vec![Condition::NoOrder, Condition::Open], None),
+        choice("Loan?", vec![Condition::MaxCash(500)], None),
+        choice("Bye", vec![], None),
+    ]);
+    let node = &set.dialogues["boss"].nodes["start"];
+    let texts = |state: DialogueState| -> Vec<String> {
+        node.available_choices(&state)
+            .map(|choice| choice.text.clone())
+            .collect()
+    };
+
+    let rich_at_night = DialogueState {
+        cash: 10_000,
+        ..Default::default()
+    };
+    assert_eq!(texts(rich_at_night), ["Bye"]);
+    let broke_by_day = DialogueState {
+        cash: 100,
+        open: true,
+        ..Default::default()
+    };
+    assert_eq!(texts(broke_by_day), ["Work?", "Loan?", "Bye"]);
+}
+
+#[test]
+fn reputation_conditions_filter_choices() {
+    let set = single_node(vec![
+        choice("Regular?", vec![Condition::MinReputation(75.)], None),
+        choice("Who are you?", vec![Condition::MaxReputation(25.)], None),
+        choice("Bye", vec![], None),
+    ]);
+    let node = &set.dialogues["boss"].nodes["start"];
+    let texts = |reputation: f32| -> Vec<String> {
+        let state = DialogueState {
+            reputation,
+            ..Default::default()
+        };
+        node.available_choices(&state)
+            .map(|choice| choice.text.clone())
+            .collect()
+    };
+
+    assert_eq!(texts(80.), ["Regular?", "Bye"]);
+    assert_eq!(texts(75.), ["Regular?", "Bye"]);
+    assert_eq!(texts(50.), ["Bye"]);
+    assert_eq!(texts(10.), ["Who are you?", "Bye"]);
+}
+
+#[test]
+fn bundled_choices_are_never_offered_twice() {
+    let set = DialogueSet::load(Path::new("assets/dialogues.ron")).unwrap();
+    let mut states = Vec::new();
+    for flags in 0..16 {
+        for cash in [0, 450, 10_000] {
+            states.push(DialogueState {
+                cash,
+                has_order: flags & 1 != 0,
+                picked_up: flags & 2 != 0,
+                open: flags & 4 != 0,
+                waiting: flags & 8 != 0,
+                ..Default::default()
+            });
+        }
+    }
+    for (name, dialogue) in &set.dialogues {
+        for (id, node) in &dialogue.nodes {
+            for state in &states {
+                let mut texts: Vec<_> = node
+                    .available_choices(state)
+                    .map(|choice| &choice.text)
+                    .collect();
+                let offered = texts.len();
+                texts.sort();
+                texts.dedup();
+                assert_eq!(texts.len(), offered, "{name}/{id} with {state:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use beer_delivery::game::economy::{payout, Money};
+
+#[test]
+fn distance_adds_to_the_payout() {
+    assert_eq!(payout(Money::dollars(10), 0., 0.), Money::dollars(10));
+    // 20 cents for every tile.
+    assert_eq!(payout(Money::dollars(10), 10., 0.), Money::dollars(12));
+}
+
+#[test]
+fn lateness_reduces_the_payout() {
+    // 2% less for every second after the deadline.
+    assert_eq!(payout(Money::dollars(10), 0., 10.), Money::dollars(8));
+    assert!(payout(Money::dollars(10), 0., 20.) < payout(Money::dollars(10), 0., 10.));
+    // Being early doesn't pay extra.
+    assert_eq!(payout(Money::dollars(10), 0., -30.), Money::dollars(10));
+}
+
+#[test]
+fn late_payout_doesnt_drop_below_a_quarter() {
+    assert_eq!(payout(Money::dollars(10), 0., 1000.), Money::cents(250));
+    assert_eq!(payout(Money::dollars(10), 10., 1000.), Money::dollars(3));
+}
@@ -0,0 +1,26 @@
+use beer_delivery::map::generator::CityGenerator;
+
+#[test]
+fn same_seed_generates_same_city() {
+    let city = CityGenerator::new(42).generate();
+    let again = CityGenerator::new(42).generate();
+    assert_eq!(city.decoration, again.decoration);
+    assert_eq!(city.solid, again.solid);
+    assert_eq!(city.delivery_points, again.delivery_points);
+
+    let other = CityGenerator::new(43).generate();
+    assert!(city.decoration != other.decoration || city.solid != other.solid);
+}
+
+#[test]
+fn block_count_is_kept_deterministic() {
+    let generator = CityGenerator::new(7).with_blocks(2, 3);
+    assert_eq!(
+        generator.generate().decoration,
+        generator.generate().decoration
+    );
+    assert_eq!(
+        generator.generate().delivery_points,
+        generator.generate().delivery_points
+    );
+}
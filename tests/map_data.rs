@@ -0,0 +1,260 @@
+use beer_delivery::map::{
+    bounds::{Bounds, Location},
+    edit::OutOfBounds,
+    tile::DecorationTile,
+    MapData,
+};
+
+fn tile(texture: u32) -> Option<DecorationTile> {
+    Some(DecorationTile { texture })
+}
+
+/// Creates map where every tile has texture equal to its index.
+fn numbered(width: usize, height: usize) -> MapData<DecorationTile> {
+    let mut map = MapData::new(width, height, String::from("city_tiles.png"));
+    for y in 0..height {
+        for x in 0..width {
+            map.set(x, y, tile((x + y * width) as u32)).unwrap();
+        }
+    }
+    map
+}
+
+fn textures(map: &MapData<DecorationTile>) -> Vec<Option<u32>> {
+    map.tiles()
+        .map(|(_, tile)| tile.map(|tile| tile.texture))
+        .collect()
+}
+
+#[test]
+fn get_and_set_are_checked() {
+    let mut map = MapData::<DecorationTile>::new(2, 3, String::new());
+    assert_eq!(map.set(1, 2, tile(5)), Ok(None));
+    assert_eq!(map.set(1, 2, tile(6)), Ok(tile(5)));
+    assert_eq!(map.get(1, 2), tile(6).as_ref());
+    assert_eq!(map.get(0, 0), None);
+    assert_eq!(map.get(2, 0), None);
+    assert_eq!(map.set(0, 3, tile(1)), Err(OutOfBounds { x: 0, y: 3 }));
+}
+
+#[test]
+fn tiles_are_iterated_with_coordinates() {
+    let map = numbered(3, 2);
+    let positions: Vec<_> = map.tiles().map(|(position, _)| position).collect();
+    assert_eq!(
+        positions,
+        vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+    );
+    for ((x, y), tile) in map.tiles() {
+        assert_eq!(tile.unwrap().texture, (x + y * 3) as u32);
+    }
+}
+
+#[test]
+fn fill_ignores_out_of_bounds() {
+    let mut map = MapData::<DecorationTile>::new(3, 3, String::new());
+    let area = Bounds {
+        min_x: 1,
+        min_y: 1,
+        max_x: 5,
+        max_y: 5,
+    };
+    map.fill(&area, tile(7));
+    assert_eq!(
+        textures(&map),
+        vec![
+            None,
+            None,
+            None,
+            None,
+            Some(7),
+            Some(7),
+            None,
+            Some(7),
+            Some(7)
+        ]
+    );
+}
+
+#[test]
+fn flood_fill_replaces_connected_tiles() {
+    let mut map = MapData::<DecorationTile>::new(4, 3, String::new());
+    let wall = Bounds {
+        min_x: 2,
+        min_y: 0,
+        max_x: 2,
+        max_y: 2,
+    };
+    map.fill(&wall, tile(1));
+
+    assert_eq!(map.flood_fill(0, 0, tile(2)), 6);
+    assert_eq!(map.flood_fill(0, 0, tile(2)), 0);
+    assert_eq!(map.get(1, 2), tile(2).as_ref());
+    assert_eq!(map.get(2, 1), tile(1).as_ref());
+    assert_eq!(map.get(3, 1), None);
+}
+
+#[test]
+fn stamp_is_copied_and_pasted() {
+    let map = numbered(4, 4);
+    let area = Bounds {
+        min_x: 1,
+        min_y: 2,
+        max_x: 2,
+        max_y: 3,
+    };
+    let mut stamp = map.copy(&area);
+    assert_eq!(
+        textures(&stamp),
+        vec![Some(9), Some(10), Some(13), Some(14)]
+    );
+
+    stamp.set(0, 0, None).unwrap();
+    let mut target = MapData::<DecorationTile>::new(3, 3, String::new());
+    target.set(1, 1, tile(100)).unwrap();
+    target.paste(&stamp, 1, 1);
+    assert_eq!(
+        textures(&target),
+        vec![
+            None,
+            None,
+            None,
+            None,
+            Some(100),
+            Some(10),
+            None,
+            Some(13),
+            Some(14)
+        ]
+    );
+}
+
+#[test]
+fn resize_keeps_anchor_in_place() {
+    let mut map = numbered(2, 2);
+    map.resize(4, 3, Location::TopRight);
+    assert_eq!((map.width(), map.height()), (4, 3));
+    assert_eq!(map.get(2, 1), tile(0).as_ref());
+    assert_eq!(map.get(3, 2), tile(3).as_ref());
+    assert_eq!(map.get(0, 0), None);
+
+    let mut map = numbered(3, 3);
+    map.resize(1, 1, Location::Center);
+    assert_eq!(textures(&map), vec![Some(4)]);
+}
+
+#[test]
+fn map_is_flipped_and_rotated() {
+    let mut map = numbered(3, 2);
+    map.flip_horizontal();
+    assert_eq!(
+        textures(&map),
+        vec![Some(2), Some(1), Some(0), Some(5), Some(4), Some(3)]
+    );
+
+    let mut map = numbered(3, 2);
+    map.flip_vertical();
+    assert_eq!(
+        textures(&map),
+        vec![Some(3), Some(4), Some(5), Some(0), Some(1), Some(2)]
+    );
+
+    // 3 4 5      0 3
+    // 0 1 2  ->  1 4
+    //            2 5
+    let mut map = numbered(3, 2);
+    map.rotate_clockwise();
+    assert_eq!((map.width(), map.height()), (2, 3));
+    assert_eq!(
+        textures(&map),
+        vec![Some(2), Some(5), Some(1), Some(4), Some(0), Some(3)]
+    );
+}
+
+#[test]
+fn diff_lists_changed_tiles() {
+    let before = numbered(2, 2);
+    let mut after = numbered(3, 2);
+    after.set(1, 1, None).unwrap();
+
+    let diff: Vec<_> = before
+        .diff(&after)
+        .into_iter()
+        .map(|diff| (diff.x, diff.y))
+        .collect();
+    // Numbering of the wider map differs from the second row onwards.
+    assert_eq!(diff, vec![(2, 0), (0, 1), (1, 1), (2, 1)]);
+}
+
+#[test]
+fn empty_maps_can_be_edited() {
+    for (width, height) in [(0, 0), (0, 3), (3, 0)] {
+        let mut map = MapData::<DecorationTile>::new(width, height, String::new());
+        map.flip_horizontal();
+        map.flip_vertical();
+        map.rotate_clockwise();
+        map.paste(&numbered(2, 2), 0, 0);
+        assert!(textures(&map).is_empty());
+        assert_eq!(map.bounds(), None);
+    }
+}
+
+#[test]
+fn stamp_out_of_range_is_cut_off() {
+    let mut map = MapData::<DecorationTile>::new(3, 3, String::new());
+    map.paste(&numbered(2, 2), 2, 1);
+    map.paste(&numbered(2, 2), usize::MAX, usize::MAX);
+    assert_eq!(
+        textures(&map),
+        vec![None, None, None, None, None, Some(0), None, None, Some(2)]
+    );
+
+    let inverted = Bounds {
+        min_x: 2,
+        min_y: 0,
+        max_x: 1,
+        max_y: 2,
+    };
+    let stamp = map.copy(&inverted);
+    assert_eq!((stamp.width(), stamp.height()), (0, 0));
+}
+
+#[test]
+fn area_at_the_end_of_the_range_doesnt_overflow() {
+    let corner = Bounds {
+        min_x: usize::MAX,
+        min_y: usize::MAX,
+        max_x: usize::MAX,
+        max_y: usize::MAX,
+    };
+    assert_eq!((corner.width(), corner.height()), (1, 1));
+    let whole = Bounds {
+        min_x: 0,
+        min_y: 0,
+        max_x: usize::MAX,
+        max_y: 0,
+    };
+    assert_eq!(whole.width(), usize::MAX);
+
+    let mut map = MapData::<DecorationTile>::new(3, 3, String::new());
+    map.fill(&corner, Some(DecorationTile { texture: 1 }));
+    assert!(textures(&map).iter().all(Option::is_none));
+    let stamp = map.copy(&corner);
+    assert_eq!((stamp.width(), stamp.height()), (1, 1));
+    assert_eq!(stamp.get(0, 0), None);
+}
+
+#[test]
+fn incomplete_row_is_kept_when_flipping() {
+    let mut map: MapData<DecorationTile> = ron::from_str(concat!(
+        "(spritesheet: \"\", width: 2, ",
+        "tiles: [Some((texture: 0)), Some((texture: 1)), Some((texture: 2))])",
+    ))
+    .unwrap();
+    map.flip_vertical();
+    assert_eq!(textures(&map), vec![Some(0), Some(1), Some(2)]);
+
+    map.rotate_clockwise();
+    assert_eq!((map.width(), map.height()), (1, 2));
+    assert_eq!(textures(&map), vec![Some(1), Some(0)]);
+}
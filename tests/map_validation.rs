@@ -0,0 +1,66 @@
+use beer_delivery::map::{
+    tile::DecorationTile,
+    validation::{Problem, Severity, ValidationRules},
+    MapData,
+};
+use bevy_ecs_tilemap::prelude::TilemapSize;
+
+fn problems(map: &MapData<DecorationTile>, rules: &ValidationRules) -> Vec<Problem> {
+    map.validate(rules)
+        .into_iter()
+        .map(|diagnostic| diagnostic.problem)
+        .collect()
+}
+
+#[test]
+fn texture_out_of_spritesheet_is_reported_at_its_tile() {
+    let mut map = MapData::new(3, 2, String::new());
+    map.set(1, 1, Some(DecorationTile { texture: 9 })).unwrap();
+    map.set(2, 0, Some(DecorationTile { texture: 10 })).unwrap();
+    let rules = ValidationRules::new().with_texture_count(10);
+
+    let diagnostics = map.validate(&rules);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].position, Some((2, 0)));
+    assert_eq!(
+        diagnostics[0].problem,
+        Problem::TextureOutOfRange {
+            texture: 10,
+            texture_count: 10
+        }
+    );
+    assert!(diagnostics[0].is_error());
+    // Textures aren't checked unless the spritesheet is known.
+    assert!(map.validate(&ValidationRules::new()).is_empty());
+}
+
+#[test]
+fn layers_of_different_size_are_reported() {
+    let map = MapData::<DecorationTile>::new(3, 2, String::new());
+    let rules = ValidationRules::new().with_size(TilemapSize { x: 3, y: 4 });
+    assert_eq!(
+        problems(&map, &rules),
+        vec![Problem::SizeMismatch {
+            expected: (3, 4),
+            found: (3, 2)
+        }]
+    );
+
+    let rules = ValidationRules::new().with_size(map.size());
+    assert!(problems(&map, &rules).is_empty());
+}
+
+#[test]
+fn only_unusable_maps_have_errors() {
+    let empty = MapData::<DecorationTile>::new(3, 0, String::new());
+    let diagnostics = empty.validate(&ValidationRules::new());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].problem, Problem::Empty);
+    assert_eq!(diagnostics[0].severity(), Severity::Warning);
+    assert!(!diagnostics[0].is_error());
+
+    let narrow = MapData::<DecorationTile>::new(0, 3, String::new());
+    let diagnostics = narrow.validate(&ValidationRules::new());
+    assert_eq!(diagnostics[0].problem, Problem::ZeroWidth);
+    assert_eq!(diagnostics[0].severity(), Severity::Error);
+}
@@ -0,0 +1,132 @@
+use beer_delivery::map::{
+    bounds::Bounds,
+    generator::CityGenerator,
+    navigation::{Algorithm, Movement, NavGrid},
+    tile::{DecorationTile, SolidTile},
+    MapData,
+};
+
+const ASPHALT: u32 = 15 * 27 + 1;
+const SIDEWALK: u32 = 8;
+
+fn solid(overhang: bool) -> Option<SolidTile> {
+    Some(SolidTile {
+        texture: 0,
+        overhang,
+    })
+}
+
+/// Sidewalk with the road along the bottom row.
+fn layers(width: usize, height: usize) -> (MapData<DecorationTile>, MapData<SolidTile>) {
+    let mut decoration = MapData::new(width, height, String::new());
+    let bounds = decoration.bounds().unwrap();
+    decoration.fill(&bounds, Some(DecorationTile { texture: SIDEWALK }));
+    let road = Bounds { max_y: 0, ..bounds };
+    decoration.fill(&road, Some(DecorationTile { texture: ASPHALT }));
+    (decoration, MapData::new(width, height, String::new()))
+}
+
+#[test]
+fn path_goes_around_walls() {
+    let (decoration, mut solid_layer) = layers(5, 5);
+    let wall = Bounds {
+        min_x: 2,
+        min_y: 1,
+        max_x: 2,
+        max_y: 3,
+    };
+    solid_layer.fill(&wall, solid(false));
+    let grid = NavGrid::new(&decoration, &solid_layer);
+
+    let path = grid.find_path((1, 1), (3, 1), Movement::Walking).unwrap();
+    assert_eq!(path.first(), Some(&(1, 1)));
+    assert_eq!(path.last(), Some(&(3, 1)));
+    // Up to the row above the wall, over it and back down.
+    assert_eq!(path.len(), 9);
+    assert!(path
+        .iter()
+        .all(|&(x, y)| grid.is_passable(x, y, Movement::Walking)));
+    assert!(path
+        .windows(2)
+        .all(|step| step[0].0.abs_diff(step[1].0) + step[0].1.abs_diff(step[1].1) == 1));
+}
+
+#[test]
+fn overhang_does_not_block() {
+    let (decoration, mut solid_layer) = layers(3, 3);
+    solid_layer.set(1, 1, solid(true)).unwrap();
+    let grid = NavGrid::new(&decoration, &solid_layer);
+    assert!(grid.is_passable(1, 1, Movement::Walking));
+    assert_eq!(
+        grid.find_path((0, 1), (2, 1), Movement::Walking),
+        Some(vec![(0, 1), (1, 1), (2, 1)])
+    );
+}
+
+#[test]
+fn movement_decides_passable_tiles() {
+    let (decoration, solid_layer) = layers(4, 3);
+    let grid = NavGrid::new(&decoration, &solid_layer);
+    assert!(grid.is_passable(0, 0, Movement::Driving));
+    assert!(grid.is_passable(0, 1, Movement::Walking));
+    // Pedestrians are able to cross the road, but they'd rather not.
+    assert!(grid.cost(0, 0, Movement::Walking) > grid.cost(0, 1, Movement::Walking));
+    assert!(grid.find_path((0, 0), (3, 0), Movement::Driving).is_some());
+    assert_eq!(grid.find_path((0, 0), (0, 2), Movement::Driving), None);
+    assert_eq!(grid.passable(Movement::Driving).count(), 4);
+}
+
+#[test]
+fn jump_point_search_finds_shortest_paths() {
+    let city = CityGenerator::new(7).generate();
+    let grid = NavGrid::new(&city.decoration, &city.solid);
+    let roads: Vec<_> = grid.passable(Movement::Driving).step_by(13).collect();
+    for &from in &roads {
+        for &to in &roads {
+            let a_star = grid.find_path(from, to, Movement::Driving);
+            let jump_point = grid.find_path_with(from, to, Movement::Driving, Algorithm::JumpPoint);
+            assert_eq!(
+                a_star.as_ref().map(Vec::len),
+                jump_point.as_ref().map(Vec::len),
+                "{from:?} -> {to:?}"
+            );
+            if let Some(path) = jump_point {
+                assert!(path
+                    .iter()
+                    .all(|&(x, y)| grid.is_passable(x, y, Movement::Driving)));
+            }
+        }
+    }
+}
+
+#[test]
+fn penalty_moves_path_elsewhere() {
+    let (decoration, solid_layer) = layers(3, 3);
+    let mut grid = NavGrid::new(&decoration, &solid_layer);
+    grid.set_penalty(1, 1, 100).unwrap();
+    let path = grid.find_path((0, 1), (2, 1), Movement::Walking).unwrap();
+    assert!(!path.contains(&(1, 1)));
+    assert!(grid.set_penalty(3, 0, 1).is_err());
+}
+
+#[test]
+fn grid_follows_layer_changes() {
+    let (decoration, solid_layer) = layers(4, 3);
+    let mut grid = NavGrid::new(&decoration, &solid_layer);
+
+    let (mut paved, mut walled) = layers(4, 3);
+    walled.set(1, 1, solid(false)).unwrap();
+    grid.update_solid(solid_layer.diff(&walled));
+    assert!(!grid.is_passable(1, 1, Movement::Walking));
+
+    paved
+        .set(2, 2, Some(DecorationTile { texture: ASPHALT }))
+        .unwrap();
+    grid.update_decoration(decoration.diff(&paved));
+    assert!(grid.is_passable(2, 2, Movement::Driving));
+
+    let rebuilt = NavGrid::new(&paved, &walled);
+    for movement in [Movement::Walking, Movement::Driving] {
+        assert!(grid.passable(movement).eq(rebuilt.passable(movement)));
+    }
+}
@@ -0,0 +1,52 @@
+use beer_delivery::{
+    replay::{Actions, Replay, ReplayCoop, ReplayMap, Session},
+    save::SaveGame,
+};
+
+fn session() -> Session {
+    Session {
+        map: ReplayMap::Random(7),
+        level: None,
+        arcade: false,
+        coop: Some(ReplayCoop::Competitive),
+        save: SaveGame::default(),
+    }
+}
+
+#[test]
+fn steps_are_played_in_recorded_order() {
+    let walk = [Actions::UP | Actions::RUN, Actions::NONE];
+    let interact = [Actions::INTERACT, Actions::LEFT];
+    let mut replay = Replay::new(session(), 42);
+    assert!(replay.is_empty());
+    for actions in [walk, walk, walk, interact, walk] {
+        replay.push(&actions);
+    }
+
+    assert_eq!(replay.len(), 5);
+    let steps: Vec<_> = replay.steps().collect();
+    assert_eq!(steps, [&walk, &walk, &walk, &interact, &walk]);
+}
+
+#[test]
+fn replay_round_trips() {
+    let path = std::env::temp_dir().join("beer_delivery_replay.beer_replay");
+    let mut replay = Replay::new(session(), 42);
+    replay.push(&[Actions::DOWN, Actions::CONFIRM]);
+    replay.push(&[Actions::NONE, Actions::NONE]);
+    replay.save(&path).unwrap();
+    assert_eq!(Replay::load(&path).unwrap(), replay);
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(Replay::load(&path).is_err());
+}
+
+#[test]
+fn actions_start_when_first_performed() {
+    let previous = Actions::UP | Actions::INTERACT;
+    let current = Actions::UP | Actions::INTERACT | Actions::RUN;
+    assert_eq!(current.started_since(previous), Actions::RUN);
+    assert!(current.contains(Actions::UP | Actions::RUN));
+    assert!(!previous.contains(Actions::UP | Actions::RUN));
+    assert!(previous.intersects(Actions::RUN | Actions::INTERACT));
+}
@@ -0,0 +1,72 @@
+use beer_delivery::{
+    reputation::{self, DistrictReputation, Outcome, MAX_SCORE, NEUTRAL_SCORE},
+    save::SaveGame,
+};
+
+#[test]
+fn deliveries_change_customer_and_district() {
+    let mut district = DistrictReputation::default();
+    district.record(
+        "preset",
+        (3, 4),
+        Outcome::Delivered {
+            late: false,
+            broken: 0,
+        },
+    );
+    assert!(district.customer("preset", (3, 4)) > NEUTRAL_SCORE);
+    assert!(district.score > NEUTRAL_SCORE);
+    assert_eq!(district.customer("preset", (5, 4)), NEUTRAL_SCORE);
+
+    let before = district.customer("preset", (3, 4));
+    district.record(
+        "preset",
+        (3, 4),
+        Outcome::Delivered {
+            late: true,
+            broken: 2,
+        },
+    );
+    assert!(district.customer("preset", (3, 4)) < before);
+
+    for _ in 0..20 {
+        district.record("preset", (3, 4), Outcome::Cancelled);
+    }
+    assert_eq!(district.customer("preset", (3, 4)), 0.);
+    assert!(district.score >= 0.);
+}
+
+#[test]
+fn customers_on_other_maps_are_kept_apart() {
+    let mut district = DistrictReputation::default();
+    district.record("city-7", (3, 4), Outcome::Cancelled);
+    assert!(district.customer("city-7", (3, 4)) < NEUTRAL_SCORE);
+    assert_eq!(district.customer("preset", (3, 4)), NEUTRAL_SCORE);
+    // District score is shared by all of its maps.
+    assert!(district.score < NEUTRAL_SCORE);
+}
+
+#[test]
+fn reputation_affects_orders_and_tips() {
+    assert!(reputation::order_weight(0.) > 0.);
+    assert!(reputation::order_weight(MAX_SCORE) > reputation::order_weight(NEUTRAL_SCORE));
+    assert_eq!(reputation::tip_multiplier(NEUTRAL_SCORE), 1.);
+}
+
+#[test]
+fn reputation_is_saved() {
+    let path = std::env::temp_dir().join("beer_delivery_reputation_save.ron");
+    let mut save = SaveGame::default();
+    let mut district = DistrictReputation::default();
+    district.record("preset", (1, 2), Outcome::Cancelled);
+    save.reputation
+        .districts
+        .insert(String::from("Old Town"), district);
+    save.save(&path).unwrap();
+    assert_eq!(SaveGame::load(&path).unwrap(), save);
+    assert_eq!(
+        save.reputation.district("Harbour"),
+        DistrictReputation::default()
+    );
+    std::fs::remove_file(&path).unwrap();
+}
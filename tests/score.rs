@@ -0,0 +1,92 @@
+use beer_delivery::{
+    campaign::LevelStats,
+    score::{Leaderboard, Score, Scoring, TABLE_SIZE},
+};
+
+fn stats(deliveries: u32) -> LevelStats {
+    LevelStats {
+        deliveries,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn score_rewards_punctuality_and_punishes_breakage() {
+    let on_time = LevelStats {
+        deliveries: 4,
+        earnings: 2000,
+        ..Default::default()
+    };
+    let late = LevelStats { late: 2, ..on_time };
+    assert_eq!(on_time.on_time_ratio(), 1.);
+    assert_eq!(late.on_time_ratio(), 0.5);
+    assert!(Score::new(&on_time).total() > Score::new(&late).total());
+
+    let broken = LevelStats {
+        broken: 3,
+        ..on_time
+    };
+    assert_eq!(Score::new(&broken).breakage, -75);
+
+    let disaster = LevelStats {
+        cancellations: 10,
+        earnings: -5000,
+        ..Default::default()
+    };
+    assert_eq!(Score::new(&disaster).total(), 0);
+}
+
+#[test]
+fn arcade_only_counts_earnings() {
+    let stats = LevelStats {
+        deliveries: 3,
+        broken: 5,
+        earnings: 4250,
+        ..Default::default()
+    };
+    assert_eq!(Scoring::Earnings.score(&stats), 42);
+    assert!(Scoring::Shift.score(&stats) > 42);
+}
+
+#[test]
+fn leaderboard_keeps_best_scores_per_table() {
+    let mut leaderboard = Leaderboard::default();
+    assert_eq!(
+        leaderboard.record("City 7", Scoring::Shift, stats(2)),
+        Some(0)
+    );
+    assert_eq!(
+        leaderboard.record("City 7", Scoring::Shift, stats(4)),
+        Some(0)
+    );
+    assert_eq!(
+        leaderboard.record("City 7", Scoring::Shift, stats(2)),
+        Some(2)
+    );
+    assert_eq!(
+        leaderboard.record("Opening day", Scoring::Shift, stats(1)),
+        Some(0)
+    );
+    assert_eq!(leaderboard.tables["City 7"].len(), 3);
+
+    for _ in 0..TABLE_SIZE {
+        leaderboard.record("City 7", Scoring::Shift, stats(5));
+    }
+    assert_eq!(leaderboard.tables["City 7"].len(), TABLE_SIZE);
+    assert_eq!(leaderboard.record("City 7", Scoring::Shift, stats(1)), None);
+    assert!(leaderboard.tables["City 7"]
+        .iter()
+        .all(|high_score| high_score.stats.deliveries == 5));
+}
+
+#[test]
+fn inconsistent_stats_dont_overflow() {
+    // Stats from a hand-edited or corrupted save may count more late deliveries than there are.
+    let stats = LevelStats {
+        deliveries: 1,
+        late: 3,
+        ..Default::default()
+    };
+    assert_eq!(Score::new(&stats).on_time, 0);
+    assert_eq!(stats.on_time_ratio(), 0.);
+}
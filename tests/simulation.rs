@@ -0,0 +1,256 @@
+use beer_delivery::{
+    game::{
+        cargo::{Cargo, Carrier},
+        clock::GameClock,
+        input::SampledInput,
+        map::{world_to_tile, MapSelection},
+        order::{ActiveOrders, OrderPool, OrderRequest},
+        player::{walkable, Coop, Player, FEET_OFFSET},
+        GameplayPlugins,
+    },
+    map::{
+        delivery::DeliveryPointKind,
+        navigation::{Movement, NavGrid},
+        tile::SolidTile,
+        MapData,
+    },
+    replay::Actions,
+    GameState,
+};
+use bevy::prelude::*;
+
+/// Game on the preset map without a window, which hasn't started yet.
+fn headless() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_state::<GameState>()
+        .add_plugins(GameplayPlugins);
+    app
+}
+
+fn start(app: &mut App) {
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Game);
+    app.update();
+}
+
+/// Simulates provided amount of steps while the courier performs the actions.
+fn play(app: &mut App, actions: Actions, steps: usize) {
+    app.world.resource_mut::<SampledInput>().hold(0, actions);
+    for _ in 0..steps {
+        app.world.run_schedule(CoreSchedule::FixedUpdate);
+    }
+}
+
+fn courier(app: &mut App) -> Vec2 {
+    app.world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+        .translation
+        .truncate()
+}
+
+/// Makes a new order come in, once customers are open after a few seconds.
+fn request_order(app: &mut App) {
+    app.world.resource_mut::<GameClock>().minutes_per_second = 60.;
+    start(app);
+    app.world
+        .resource_mut::<Events<OrderRequest>>()
+        .send(OrderRequest::New(0));
+    play(app, Actions::NONE, 90);
+}
+
+#[test]
+fn courier_walks_in_held_direction() {
+    let mut app = headless();
+    start(&mut app);
+    play(&mut app, Actions::LEFT, 60);
+
+    let position = courier(&mut app);
+    assert!((position.x + 50.).abs() < 0.01, "{position}");
+    assert_eq!(position.y, 0.);
+}
+
+#[test]
+fn courier_cant_pass_through_preset_building() {
+    let mut app = headless();
+    start(&mut app);
+    // Building of the brewery starts right above the courier.
+    play(&mut app, Actions::UP | Actions::RUN, 120);
+    let position = courier(&mut app);
+    assert!(position.y > 0. && position.y < 2., "{position}");
+
+    // Going around it is possible.
+    play(&mut app, Actions::RIGHT | Actions::RUN, 60);
+    play(&mut app, Actions::UP, 60);
+    assert!(courier(&mut app).y > 40.);
+}
+
+/// Checks that the courier with provided index doesn't collide with anything and that its feet
+/// are on a tile that it's able to walk on.
+fn stands_on_passable_tile(app: &mut App, index: usize) -> bool {
+    let position = app
+        .world
+        .query::<(&Player, &Transform)>()
+        .iter(&app.world)
+        .find(|(player, _)| player.index == index)
+        .map(|(_, transform)| transform.translation.truncate())
+        .unwrap();
+    let size = app
+        .world
+        .query::<&MapData<SolidTile>>()
+        .single(&app.world)
+        .size();
+    let map = app.world.query::<&MapData<SolidTile>>().single(&app.world);
+    if !walkable(map, position) {
+        return false;
+    }
+    world_to_tile(size, position + FEET_OFFSET).is_some_and(|(x, y)| {
+        app.world
+            .resource::<NavGrid>()
+            .is_passable(x, y, Movement::Walking)
+    })
+}
+
+#[test]
+fn courier_doesnt_start_inside_a_building() {
+    // Centre of these cities is covered by a building.
+    for seed in [114, 150] {
+        let mut app = headless();
+        app.insert_resource(MapSelection::Random(seed));
+        start(&mut app);
+        play(&mut app, Actions::NONE, 2);
+        assert!(stands_on_passable_tile(&mut app, 0), "seed {seed}");
+    }
+}
+
+#[test]
+fn second_courier_starts_next_to_the_first_one() {
+    let mut app = headless();
+    app.insert_resource(Coop(Some(OrderPool::Shared)));
+    start(&mut app);
+    play(&mut app, Actions::NONE, 2);
+
+    let mut players = app.world.query::<(&Player, &Transform)>();
+    let positions: Vec<_> = players
+        .iter(&app.world)
+        .map(|(player, transform)| (player.index, transform.translation.truncate()))
+        .collect();
+    let first = positions.iter().find(|(index, _)| *index == 0).unwrap().1;
+    let second = positions.iter().find(|(index, _)| *index == 1).unwrap().1;
+    assert!(first.distance(second) < 24., "{first} {second}");
+    assert!(stands_on_passable_tile(&mut app, 1));
+}
+
+#[test]
+fn grid_follows_changes_of_the_map() {
+    let mut app = headless();
+    start(&mut app);
+    play(&mut app, Actions::NONE, 1);
+    assert!(app
+        .world
+        .resource::<NavGrid>()
+        .is_passable(2, 2, Movement::Walking));
+
+    let mut solid = app.world.query::<&mut MapData<SolidTile>>();
+    solid
+        .single_mut(&mut app.world)
+        .set(
+            2,
+            2,
+            Some(SolidTile {
+                texture: 0,
+                overhang: false,
+            }),
+        )
+        .unwrap();
+    play(&mut app, Actions::NONE, 1);
+    assert!(!app
+        .world
+        .resource::<NavGrid>()
+        .is_passable(2, 2, Movement::Walking));
+}
+
+#[test]
+fn order_is_picked_up_at_brewery() {
+    let mut app = headless();
+    request_order(&mut app);
+    let order = app
+        .world
+        .resource::<ActiveOrders>()
+        .get(0)
+        .cloned()
+        .unwrap();
+    assert_eq!(order.pickup.kind, DeliveryPointKind::Brewery);
+    assert!(!order.picked_up());
+
+    // Courier starts next to the door of the brewery.
+    play(&mut app, Actions::INTERACT, 3);
+    assert!(app
+        .world
+        .resource::<ActiveOrders>()
+        .get(0)
+        .unwrap()
+        .picked_up());
+    let cargo = app
+        .world
+        .query_filtered::<&Cargo, With<Player>>()
+        .single(&app.world);
+    assert!(!cargo.is_empty());
+}
+
+#[test]
+fn beer_of_cancelled_order_is_returned() {
+    let mut app = headless();
+    request_order(&mut app);
+    play(&mut app, Actions::INTERACT, 3);
+    app.world
+        .resource_mut::<Events<OrderRequest>>()
+        .send(OrderRequest::Cancel(0));
+    play(&mut app, Actions::NONE, 1);
+    assert!(app.world.resource::<ActiveOrders>().get(0).is_none());
+    let cargo = app
+        .world
+        .query_filtered::<&Cargo, With<Player>>()
+        .single(&app.world);
+    assert!(cargo.is_empty());
+    assert_eq!(cargo.carrier(), Carrier::OnFoot);
+
+    // Next order is picked up just like the first one.
+    for _ in 0..3 {
+        app.world
+            .resource_mut::<Events<OrderRequest>>()
+            .send(OrderRequest::New(0));
+        play(&mut app, Actions::NONE, 10);
+        play(&mut app, Actions::INTERACT, 3);
+        let orders = app.world.resource::<ActiveOrders>();
+        assert!(orders.get(0).unwrap().picked_up());
+        app.world
+            .resource_mut::<Events<OrderRequest>>()
+            .send(OrderRequest::Cancel(0));
+        play(&mut app, Actions::NONE, 1);
+    }
+}
+
+#[test]
+fn same_inputs_play_out_the_same_way() {
+    let mut games = [headless(), headless()];
+    for app in &mut games {
+        request_order(app);
+        play(app, Actions::RIGHT | Actions::RUN, 45);
+        play(app, Actions::UP, 100);
+    }
+
+    let [first, second] = &mut games;
+    assert_eq!(courier(first), courier(second));
+    let items = |app: &App| {
+        app.world
+            .resource::<ActiveOrders>()
+            .get(0)
+            .unwrap()
+            .items
+            .clone()
+    };
+    assert_eq!(items(first), items(second));
+}